@@ -1,7 +1,16 @@
 //! Backends for `ugly`.
 //!
-//! At the time of writing, only one backend exists: SDL2, enabled by the `sdl2` feature and residing in the `ugly::backends::sdl` module.
+//! At the time of writing, the following backends exist:
+//!
+//! - SDL2, enabled by the `sdl2` feature and residing in the `ugly::backends::sdl` module;
+//! - `wgpu`, a hardware-accelerated backend residing in the `ugly::backends::wgpu` module;
+//! - `software`, a CPU rasteriser that renders into an in-memory framebuffer, residing in the
+//!   `ugly::backends::software` module;
+//! - `terminal`, a text-mode backend that renders onto character cells and emits ANSI escape
+//!   sequences, residing in the `ugly::backends::terminal` module.
 
 #[cfg(feature = "sdl2")]
 pub mod sdl;
+pub mod software;
+pub mod terminal;
 pub mod wgpu;
@@ -0,0 +1,25 @@
+//! The SDL2 backend.
+//!
+//! This backend renders onto an SDL2 window canvas.  Font textures are decoded with `image`
+//! rather than `SDL2_image`, so only the core SDL2 library is needed.
+
+mod font;
+mod render;
+
+pub use render::Renderer;
+
+/// Converts an `ugly` rectangle into an SDL one, clamping negative sizes to zero.
+fn convert_rect(rect: crate::metrics::Rect) -> sdl2::rect::Rect {
+    let size = rect.size.clamp();
+    sdl2::rect::Rect::new(
+        rect.top_left.x,
+        rect.top_left.y,
+        u32::try_from(size.w).unwrap_or_default(),
+        u32::try_from(size.h).unwrap_or_default(),
+    )
+}
+
+/// Converts an `ugly` colour into an SDL one.
+fn convert_colour(colour: crate::colour::Definition) -> sdl2::pixels::Color {
+    sdl2::pixels::Color::RGBA(colour.r, colour.g, colour.b, colour.a)
+}
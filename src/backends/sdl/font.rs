@@ -0,0 +1,28 @@
+//! Font loading for SDL2.
+
+use sdl2::{
+    pixels::PixelFormatEnum,
+    render::{BlendMode, Texture, TextureCreator},
+    video::WindowContext,
+};
+
+use crate::font;
+
+/// Loads the unscaled texture of `font` into an SDL texture owned by `creator`.
+pub(super) fn load<'a>(
+    creator: &'a TextureCreator<WindowContext>,
+    font: &font::Font,
+) -> font::Result<Texture<'a>> {
+    let image = font.unscaled_texture()?;
+    let (width, height) = image.dimensions();
+
+    let mut texture = creator
+        .create_texture_static(PixelFormatEnum::RGBA32, width, height)
+        .map_err(|e| font::Error::TextureLoad(e.to_string()))?;
+    texture
+        .update(None, &image, 4 * width as usize)
+        .map_err(|e| font::Error::TextureLoad(e.to_string()))?;
+    texture.set_blend_mode(BlendMode::Blend);
+
+    Ok(texture)
+}
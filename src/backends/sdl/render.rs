@@ -0,0 +1,141 @@
+//! Rendering using SDL2.
+
+use sdl2::{
+    render::{BlendMode, Canvas, Texture, TextureCreator},
+    video::{Window, WindowContext},
+};
+
+use crate::{
+    colour,
+    font::{self, Metrics},
+    metrics, resource, Error, Result,
+};
+
+use super::{convert_colour, convert_rect};
+
+/// A renderer that draws onto an SDL2 window canvas.
+///
+/// Font textures are created using a borrowed texture creator, and cached in a
+/// [`font::Manager`]; `'a` is the lifetime of that texture creator.
+pub struct Renderer<'a, Font, Fg, Bg>
+where
+    Font: font::Map,
+{
+    canvas: Canvas<Window>,
+    texture_creator: &'a TextureCreator<WindowContext>,
+
+    font_manager: font::Manager<Font, Texture<'a>>,
+    palette: colour::Palette<Fg, Bg>,
+}
+
+impl<Font, Fg, Bg> crate::ui::layout::LayoutContext<Font::Id> for Renderer<'_, Font, Fg, Bg>
+where
+    Font: font::Map,
+{
+    fn font_metrics(&self) -> &impl resource::Map<Metrics, Id = Font::Id> {
+        self.font_manager.metrics()
+    }
+}
+
+impl<Font, Fg, Bg> crate::Renderer<Font::Id, Fg::Id, Bg::Id> for Renderer<'_, Font, Fg, Bg>
+where
+    Font: font::Map,
+    Fg: resource::Map<colour::Definition>,
+    Bg: resource::Map<colour::Definition>,
+{
+    fn write(
+        &mut self,
+        fonts: &font::Chain<Font::Id>,
+        colour: Fg::Id,
+        str: &font::layout::String,
+        offset: metrics::point::Delta,
+    ) -> Result<()> {
+        let colour = *self.palette.fg.get(colour);
+        let origin = str.bounds.top_left.offset(offset.dx, offset.dy);
+        let creator = self.texture_creator;
+
+        // Blending depends on drawing order, so we draw glyphs in the order they were laid out.
+        for glyph in str.glyphs.in_order() {
+            let Some(font) = fonts.get(glyph.font) else {
+                continue;
+            };
+            let src = self.font_manager.texture_rect(font, glyph.src);
+            let texture = self
+                .font_manager
+                .data(font, |p| super::font::load(creator, p))?;
+            texture.set_color_mod(colour.r, colour.g, colour.b);
+            texture.set_alpha_mod(colour.a);
+
+            let top_left = origin.offset(glyph.dst.dx, glyph.dst.dy);
+            let src = convert_rect(src);
+            let dst = convert_rect(top_left.to_rect(glyph.src.size, metrics::Anchor::TOP_LEFT));
+            self.canvas
+                .copy(texture, src, dst)
+                .map_err(Error::Backend)?;
+        }
+
+        self.canvas.set_draw_color(convert_colour(colour));
+        for rect in &str.decorations {
+            let top_left = origin.offset(rect.top_left.x, rect.top_left.y);
+            let dst = convert_rect(top_left.to_rect(rect.size, metrics::Anchor::TOP_LEFT));
+            self.canvas.fill_rect(dst).map_err(Error::Backend)?;
+        }
+
+        Ok(())
+    }
+
+    fn fill(&mut self, rect: metrics::Rect, colour: Bg::Id) -> Result<()> {
+        let colour = *self.palette.bg.get(colour);
+        self.canvas.set_draw_color(convert_colour(colour));
+        self.canvas
+            .fill_rect(convert_rect(rect))
+            .map_err(Error::Backend)
+    }
+
+    fn clear(&mut self, colour: Bg::Id) -> Result<()> {
+        let colour = *self.palette.bg.get(colour);
+        self.canvas.set_draw_color(convert_colour(colour));
+        self.canvas.clear();
+        Ok(())
+    }
+
+    fn present(&mut self) {
+        self.canvas.present();
+    }
+}
+
+impl<'a, Font, Fg, Bg> Renderer<'a, Font, Fg, Bg>
+where
+    Font: font::Map,
+    Fg: resource::Map<colour::Definition>,
+    Bg: resource::Map<colour::Definition>,
+{
+    /// Constructs a new SDL2 renderer over `canvas`.
+    ///
+    /// `texture_creator` should come from `canvas`, and will be used to load font textures.
+    #[must_use]
+    pub fn new(
+        mut canvas: Canvas<Window>,
+        texture_creator: &'a TextureCreator<WindowContext>,
+        resources: resource::Set<Font, Fg, Bg>,
+    ) -> Self {
+        canvas.set_blend_mode(BlendMode::Blend);
+        Self {
+            canvas,
+            texture_creator,
+            font_manager: font::Manager::new(resources.fonts, resources.metrics),
+            palette: resources.palette,
+        }
+    }
+
+    /// Borrows the underlying canvas.
+    #[must_use]
+    pub fn canvas(&self) -> &Canvas<Window> {
+        &self.canvas
+    }
+
+    /// Mutably borrows the underlying canvas.
+    pub fn canvas_mut(&mut self) -> &mut Canvas<Window> {
+        &mut self.canvas
+    }
+}
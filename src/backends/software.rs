@@ -0,0 +1,15 @@
+//! A CPU-only software rasteriser backend.
+//!
+//! This backend renders into an in-memory RGBA framebuffer (an [`image::RgbaImage`]), and so needs
+//! neither a GPU nor a window.  It is primarily useful for testing and for machines without
+//! graphics hardware.
+//!
+//! The [snapshot] module builds on this backend to render frames headlessly, for instance for
+//! golden-image tests.
+
+mod font;
+mod raster;
+mod render;
+pub mod snapshot;
+
+pub use render::Renderer;
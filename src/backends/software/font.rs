@@ -0,0 +1,8 @@
+//! Font loading for the software rasteriser.
+
+use crate::font;
+
+/// Loads the unscaled texture of `font` into an RGBA image.
+pub(super) fn load(font: &font::Font) -> font::Result<image::RgbaImage> {
+    font.unscaled_texture()
+}
@@ -0,0 +1,197 @@
+//! Low-level rasterisation primitives over RGBA images.
+//!
+//! All drawing here uses straight (non-premultiplied) alpha and the usual 'source over'
+//! compositing, which matches what the `wgpu` backend's alpha blending does.
+
+use image::{Rgba, RgbaImage};
+
+use crate::{
+    colour::Definition,
+    metrics::{Point, Rect},
+};
+
+/// Replaces every pixel in `target` with `colour`, without blending.
+pub(super) fn clear(target: &mut RgbaImage, colour: Definition) {
+    for pixel in target.pixels_mut() {
+        *pixel = to_rgba(colour);
+    }
+}
+
+/// Blends `colour` over every pixel of `target` inside `rect`.
+pub(super) fn fill(target: &mut RgbaImage, rect: Rect, colour: Definition) {
+    if colour.is_transparent() {
+        return;
+    }
+
+    for y in rect.top_left.y..rect.top_left.y + rect.size.h {
+        for x in rect.top_left.x..rect.top_left.x + rect.size.w {
+            if let Some(pixel) = pixel_mut(target, Point { x, y }) {
+                blend(pixel, colour);
+            }
+        }
+    }
+}
+
+/// Blends the `src` rectangle of `texture` onto the `dst` rectangle of `target`.
+///
+/// If `dst` is bigger than `src`, as it is for scaled fonts, the glyph is stretched with
+/// nearest-neighbour sampling.  Each texel is tinted by multiplying it by `colour`, as the `wgpu`
+/// shader does.  Parts of the glyph falling outside either image are clipped.
+pub(super) fn blit(
+    target: &mut RgbaImage,
+    texture: &RgbaImage,
+    src: Rect,
+    dst: Rect,
+    colour: Definition,
+) {
+    for dy in 0..dst.size.h {
+        for dx in 0..dst.size.w {
+            let (sx, sy) = (dx * src.size.w / dst.size.w, dy * src.size.h / dst.size.h);
+            let Some(texel) = pixel(texture, src.top_left.offset(sx, sy)) else {
+                continue;
+            };
+            if let Some(pixel) = pixel_mut(target, dst.top_left.offset(dx, dy)) {
+                blend(pixel, tint(*texel, colour));
+            }
+        }
+    }
+}
+
+/// Multiplies `texel` by `colour` component-wise.
+fn tint(texel: Rgba<u8>, colour: Definition) -> Definition {
+    let [r, g, b, a] = texel.0;
+    Definition::rgba(
+        multiply(r, colour.r),
+        multiply(g, colour.g),
+        multiply(b, colour.b),
+        multiply(a, colour.a),
+    )
+}
+
+/// Composites `src` over `dst` in place.
+fn blend(dst: &mut Rgba<u8>, src: Definition) {
+    let src_a = u32::from(src.a);
+    let dst_a = u32::from(dst.0[3]) * (255 - src_a) / 255;
+    let out_a = src_a + dst_a;
+    if out_a == 0 {
+        *dst = to_rgba(crate::colour::definition::TRANSPARENT);
+        return;
+    }
+
+    let mix = |s: u8, d: u8| {
+        let c = (u32::from(s) * src_a + u32::from(d) * dst_a) / out_a;
+        // `c` is a weighted average of two bytes, so it can't exceed 255.
+        u8::try_from(c).unwrap_or(u8::MAX)
+    };
+
+    let [dr, dg, db, _] = dst.0;
+    *dst = Rgba([
+        mix(src.r, dr),
+        mix(src.g, dg),
+        mix(src.b, db),
+        u8::try_from(out_a).unwrap_or(u8::MAX),
+    ]);
+}
+
+/// Multiplies two bytes as if they were fractions of 255.
+fn multiply(x: u8, y: u8) -> u8 {
+    u8::try_from(u32::from(x) * u32::from(y) / 255).unwrap_or(u8::MAX)
+}
+
+fn to_rgba(colour: Definition) -> Rgba<u8> {
+    Rgba([colour.r, colour.g, colour.b, colour.a])
+}
+
+fn pixel(image: &RgbaImage, point: Point) -> Option<&Rgba<u8>> {
+    let (x, y) = image_coords(image, point)?;
+    Some(image.get_pixel(x, y))
+}
+
+fn pixel_mut(image: &mut RgbaImage, point: Point) -> Option<&mut Rgba<u8>> {
+    let (x, y) = image_coords(image, point)?;
+    Some(image.get_pixel_mut(x, y))
+}
+
+/// Converts `point` into image coordinates, if it is inside `image`.
+fn image_coords(image: &RgbaImage, point: Point) -> Option<(u32, u32)> {
+    let x = u32::try_from(point.x).ok()?;
+    let y = u32::try_from(point.y).ok()?;
+    (x < image.width() && y < image.height()).then_some((x, y))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::colour::EGA;
+
+    /// Filling with an opaque colour replaces the pixels inside the rect, and only those.
+    #[test]
+    fn fill_opaque() {
+        let mut image = RgbaImage::new(4, 4);
+        fill(&mut image, Rect::new(1, 1, 2, 2), EGA.bright.red);
+
+        assert_eq!(to_rgba(EGA.bright.red), *image.get_pixel(1, 1));
+        assert_eq!(to_rgba(EGA.bright.red), *image.get_pixel(2, 2));
+        assert_eq!(Rgba([0, 0, 0, 0]), *image.get_pixel(0, 0));
+        assert_eq!(Rgba([0, 0, 0, 0]), *image.get_pixel(3, 3));
+    }
+
+    /// Filling a rect that hangs off the edges of the image clips rather than panicking.
+    #[test]
+    fn fill_clipped() {
+        let mut image = RgbaImage::new(2, 2);
+        fill(&mut image, Rect::new(-5, -5, 20, 20), EGA.dark.blue);
+
+        assert!(image.pixels().all(|p| *p == to_rgba(EGA.dark.blue)));
+    }
+
+    /// Half-transparent colours blend with what is underneath.
+    #[test]
+    fn blend_half_alpha() {
+        let mut pixel = Rgba([0, 0, 0, 255]);
+        blend(&mut pixel, Definition::rgba(255, 255, 255, 128));
+
+        assert_eq!(Rgba([128, 128, 128, 255]), pixel);
+    }
+
+    /// Blitting tints the texture and skips transparent texels.
+    #[test]
+    fn blit_tinted() {
+        let mut texture = RgbaImage::new(2, 1);
+        texture.put_pixel(0, 0, Rgba([255, 255, 255, 255]));
+
+        let mut image = RgbaImage::new(3, 3);
+        blit(
+            &mut image,
+            &texture,
+            Rect::new(0, 0, 2, 1),
+            Rect::new(1, 2, 2, 1),
+            EGA.bright.green,
+        );
+
+        assert_eq!(to_rgba(EGA.bright.green), *image.get_pixel(1, 2));
+        assert_eq!(Rgba([0, 0, 0, 0]), *image.get_pixel(2, 2));
+    }
+
+    /// Blitting onto a bigger rectangle stretches the texture with nearest-neighbour sampling.
+    #[test]
+    fn blit_stretched() {
+        let mut texture = RgbaImage::new(2, 1);
+        texture.put_pixel(0, 0, Rgba([255, 255, 255, 255]));
+
+        let mut image = RgbaImage::new(4, 2);
+        blit(
+            &mut image,
+            &texture,
+            Rect::new(0, 0, 2, 1),
+            Rect::new(0, 0, 4, 2),
+            EGA.bright.green,
+        );
+
+        let lit: Vec<_> = image.pixels().map(|p| p.0[3] != 0).collect();
+        assert_eq!(
+            vec![true, true, false, false, true, true, false, false],
+            lit
+        );
+    }
+}
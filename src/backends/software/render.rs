@@ -0,0 +1,206 @@
+//! Rendering using the software rasteriser.
+
+use crate::{
+    colour,
+    font::{self, Metrics},
+    metrics, resource, Result,
+};
+
+use super::raster;
+
+/// A renderer that rasterises into an in-memory RGBA framebuffer.
+///
+/// Font textures are decoded once, on first use, and cached in a [`font::Manager`].
+pub struct Renderer<Font, Fg, Bg>
+where
+    Font: font::Map,
+{
+    framebuffer: image::RgbaImage,
+
+    font_manager: font::Manager<Font, image::RgbaImage>,
+    palette: colour::Palette<Fg, Bg>,
+}
+
+impl<Font, Fg, Bg> crate::ui::layout::LayoutContext<Font::Id> for Renderer<Font, Fg, Bg>
+where
+    Font: font::Map,
+{
+    fn font_metrics(&self) -> &impl resource::Map<Metrics, Id = Font::Id> {
+        self.font_manager.metrics()
+    }
+}
+
+impl<Font, Fg, Bg> crate::Renderer<Font::Id, Fg::Id, Bg::Id> for Renderer<Font, Fg, Bg>
+where
+    Font: font::Map,
+    Fg: resource::Map<colour::Definition>,
+    Bg: resource::Map<colour::Definition>,
+{
+    fn write(
+        &mut self,
+        fonts: &font::Chain<Font::Id>,
+        colour: Fg::Id,
+        str: &font::layout::String,
+        offset: metrics::point::Delta,
+    ) -> Result<()> {
+        let colour = *self.palette.fg.get(colour);
+        let origin = str.bounds.top_left.offset(offset.dx, offset.dy);
+
+        // Blending depends on drawing order, so we draw glyphs in the order they were laid out.
+        for glyph in str.glyphs.in_order() {
+            let Some(font) = fonts.get(glyph.font) else {
+                continue;
+            };
+            let src = self.font_manager.texture_rect(font, glyph.src);
+            let texture = self.font_manager.data(font, super::font::load)?;
+            let top_left = origin.offset(glyph.dst.dx, glyph.dst.dy);
+            let dst = top_left.to_rect(glyph.src.size, metrics::Anchor::TOP_LEFT);
+            raster::blit(&mut self.framebuffer, texture, src, dst, colour);
+        }
+
+        for rect in &str.decorations {
+            let top_left = origin.offset(rect.top_left.x, rect.top_left.y);
+            let rect = top_left.to_rect(rect.size, metrics::Anchor::TOP_LEFT);
+            raster::fill(&mut self.framebuffer, rect, colour);
+        }
+
+        Ok(())
+    }
+
+    fn fill(&mut self, rect: metrics::Rect, colour: Bg::Id) -> Result<()> {
+        raster::fill(&mut self.framebuffer, rect, *self.palette.bg.get(colour));
+        Ok(())
+    }
+
+    fn clear(&mut self, colour: Bg::Id) -> Result<()> {
+        raster::clear(&mut self.framebuffer, *self.palette.bg.get(colour));
+        Ok(())
+    }
+
+    fn present(&mut self) {
+        // Everything is drawn straight into the framebuffer, so there is nothing to flip.
+    }
+}
+
+impl<Font, Fg, Bg> Renderer<Font, Fg, Bg>
+where
+    Font: font::Map,
+    Fg: resource::Map<colour::Definition>,
+    Bg: resource::Map<colour::Definition>,
+{
+    /// Constructs a new software renderer with a transparent framebuffer of the given size.
+    ///
+    /// Negative dimensions are treated as zero.
+    #[must_use]
+    pub fn new(size: metrics::Size, resources: resource::Set<Font, Fg, Bg>) -> Self {
+        let (width, height) = image_size(size);
+        Self {
+            framebuffer: image::RgbaImage::new(width, height),
+            font_manager: font::Manager::new(resources.fonts, resources.metrics),
+            palette: resources.palette,
+        }
+    }
+
+    /// Gets the size of the framebuffer.
+    #[must_use]
+    pub fn size(&self) -> metrics::Size {
+        let (w, h) = self.framebuffer.dimensions();
+        metrics::Size {
+            w: metrics::Length::try_from(w).unwrap_or(metrics::Length::MAX),
+            h: metrics::Length::try_from(h).unwrap_or(metrics::Length::MAX),
+        }
+    }
+
+    /// Resizes the framebuffer, discarding its contents.
+    pub fn resize(&mut self, size: metrics::Size) {
+        let (width, height) = image_size(size);
+        self.framebuffer = image::RgbaImage::new(width, height);
+    }
+
+    /// Borrows the framebuffer.
+    #[must_use]
+    pub fn framebuffer(&self) -> &image::RgbaImage {
+        &self.framebuffer
+    }
+
+    /// Saves the framebuffer as a PNG at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the PNG can't be encoded or written.
+    pub fn save_png(&self, path: impl AsRef<std::path::Path>) -> Result<()> {
+        self.framebuffer
+            .save_with_format(path, image::ImageFormat::Png)
+            .map_err(|e| crate::Error::Backend(e.to_string()))
+    }
+
+    /// Consumes this renderer, returning its framebuffer.
+    #[must_use]
+    pub fn into_framebuffer(self) -> image::RgbaImage {
+        self.framebuffer
+    }
+}
+
+/// Converts a size into image dimensions, clamping negative lengths to zero.
+fn image_size(size: metrics::Size) -> (u32, u32) {
+    let size = size.clamp();
+    (
+        u32::try_from(size.w).unwrap_or_default(),
+        u32::try_from(size.h).unwrap_or_default(),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::{colour::ega, Renderer as _};
+
+    fn renderer() -> Renderer<resource::DefaultingHashMap<(), font::Font>, colour::Ega, colour::Ega>
+    {
+        let fonts = resource::DefaultingHashMap::new(HashMap::new(), font::Font::from_dir("."));
+        let resources = resource::Set::new(fonts, colour::EGA, colour::EGA)
+            .expect("an empty font map should not fail to load");
+        Renderer::new(metrics::Size { w: 8, h: 4 }, resources)
+    }
+
+    /// Clearing then filling paints the expected pixels.
+    #[test]
+    fn clear_then_fill() {
+        let mut r = renderer();
+        r.clear(ega::Id::DARK_BLUE).unwrap();
+        r.fill(metrics::Rect::new(4, 0, 4, 4), ega::Id::BRIGHT_RED)
+            .unwrap();
+        r.present();
+
+        let blue = colour::EGA.dark.blue;
+        let red = colour::EGA.bright.red;
+        assert_eq!(
+            [blue.r, blue.g, blue.b, blue.a],
+            r.framebuffer().get_pixel(0, 0).0
+        );
+        assert_eq!(
+            [red.r, red.g, red.b, red.a],
+            r.framebuffer().get_pixel(7, 3).0
+        );
+    }
+
+    /// Writing with a font whose texture is missing fails, and draws nothing.
+    #[test]
+    fn write_missing_texture() {
+        let mut r = renderer();
+        let metrics = crate::backends::terminal::metrics();
+        let str = font::layout::Builder::new(&metrics).build("a".to_owned());
+        let err = r
+            .write(
+                &().into(),
+                ega::Id::BRIGHT_WHITE,
+                &str,
+                metrics::point::Delta::default(),
+            )
+            .expect_err("font texture should fail to load");
+        assert!(matches!(err, crate::Error::Font(_)));
+        assert!(r.framebuffer().pixels().all(|p| p.0[3] == 0));
+    }
+}
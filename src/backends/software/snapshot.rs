@@ -0,0 +1,213 @@
+//! Headless rendering to images, and golden-image comparison.
+//!
+//! This module lets tests render a frame with the software [Renderer] and compare it against a
+//! committed reference PNG.  When a comparison fails, the actual image and a diff image are
+//! written next to the reference so that the failure can be inspected.
+//!
+//! Setting the environment variable named by [`UPDATE_VAR`] makes [check] overwrite references
+//! with the actual images instead of comparing against them.
+
+use std::path::{Path, PathBuf};
+
+use image::{Rgba, RgbaImage};
+use thiserror::Error;
+
+use crate::{
+    colour, font, metrics, resource,
+    ui::{layout::Boundable, Layoutable, Renderable},
+};
+
+use super::Renderer;
+
+/// Environment variable that, when set, causes [check] to update reference images.
+pub const UPDATE_VAR: &str = "UGLY_UPDATE_SNAPSHOTS";
+
+/// Renders a frame of the given size by calling `f` on a fresh software renderer.
+///
+/// The frame starts out transparent; `f` can clear it to a background colour if needed.
+///
+/// # Errors
+///
+/// Fails if `f` fails.
+pub fn render<Font, Fg, Bg>(
+    size: metrics::Size,
+    resources: resource::Set<Font, Fg, Bg>,
+    f: impl FnOnce(&mut Renderer<Font, Fg, Bg>) -> crate::Result<()>,
+) -> crate::Result<RgbaImage>
+where
+    Font: font::Map,
+    Fg: resource::Map<colour::Definition>,
+    Bg: resource::Map<colour::Definition>,
+{
+    let mut renderer = Renderer::new(size, resources);
+    f(&mut renderer)?;
+    crate::Renderer::<Font::Id, Fg::Id, Bg::Id>::present(&mut renderer);
+    Ok(renderer.into_framebuffer())
+}
+
+/// Renders a widget tree into a frame of the given size.
+///
+/// The widget is bounded to the whole frame, laid out, and then rendered.  Any updating of the
+/// widget's state should happen before calling this.
+///
+/// # Errors
+///
+/// Fails if the widget fails to render.
+pub fn render_widget<Font, Fg, Bg, W>(
+    size: metrics::Size,
+    resources: resource::Set<Font, Fg, Bg>,
+    widget: &mut W,
+) -> crate::Result<RgbaImage>
+where
+    Font: font::Map,
+    Fg: resource::Map<colour::Definition>,
+    Bg: resource::Map<colour::Definition>,
+    W: Boundable + Layoutable<Renderer<Font, Fg, Bg>> + Renderable<Renderer<Font, Fg, Bg>>,
+{
+    render(size, resources, |r| {
+        widget.set_bounds(metrics::Rect {
+            top_left: metrics::Point::default(),
+            size,
+        });
+        widget.layout(r);
+        widget.render(r)
+    })
+}
+
+/// The difference between two images.
+#[derive(Debug, Clone)]
+pub struct Diff {
+    /// The number of pixels that differ, including any pixels not present in both images.
+    pub differing_pixels: usize,
+    /// A visualisation of the difference.
+    ///
+    /// Matching pixels are shown as a faded greyscale copy of the expected image; differing
+    /// pixels are shown in opaque magenta.
+    pub image: RgbaImage,
+}
+
+/// Compares `actual` against `expected`, returning a [Diff] if they are not identical.
+#[must_use]
+pub fn diff(actual: &RgbaImage, expected: &RgbaImage) -> Option<Diff> {
+    let width = actual.width().max(expected.width());
+    let height = actual.height().max(expected.height());
+
+    let mut differing_pixels = 0;
+    let image = RgbaImage::from_fn(width, height, |x, y| {
+        let a = actual.get_pixel_checked(x, y);
+        let e = expected.get_pixel_checked(x, y);
+        match (a, e) {
+            (Some(a), Some(e)) if a == e => fade(*e),
+            _ => {
+                differing_pixels += 1;
+                DIFF_COLOUR
+            }
+        }
+    });
+
+    (0 < differing_pixels).then_some(Diff {
+        differing_pixels,
+        image,
+    })
+}
+
+/// Checks `actual` against the reference PNG at `reference`.
+///
+/// On a mismatch, this writes `actual` and the diff image next to the reference, with the
+/// extensions `actual.png` and `diff.png` respectively.  If [`UPDATE_VAR`] is set, the reference
+/// is overwritten with `actual` instead.
+///
+/// # Errors
+///
+/// Fails if the reference can't be loaded, or if the images differ.
+pub fn check(actual: &RgbaImage, reference: impl AsRef<Path>) -> Result<()> {
+    let reference = reference.as_ref();
+
+    if std::env::var_os(UPDATE_VAR).is_some() {
+        actual.save(reference)?;
+        return Ok(());
+    }
+
+    let expected = image::open(reference)?.to_rgba8();
+    let Some(diff) = diff(actual, &expected) else {
+        return Ok(());
+    };
+
+    let actual_path = reference.with_extension("actual.png");
+    let diff_path = reference.with_extension("diff.png");
+    actual.save(&actual_path)?;
+    diff.image.save(&diff_path)?;
+
+    Err(Error::Mismatch {
+        reference: reference.to_path_buf(),
+        differing_pixels: diff.differing_pixels,
+        diff: diff_path,
+    })
+}
+
+/// Fades a pixel into a light greyscale version of itself.
+fn fade(pixel: Rgba<u8>) -> Rgba<u8> {
+    let [r, g, b, a] = pixel.0.map(u32::from);
+    let luma = (r * 299 + g * 587 + b * 114) / 1000;
+    // Map into the upper half of the range so that differences stand out.
+    let grey = u8::try_from(128 + luma / 2).unwrap_or(u8::MAX);
+    Rgba([grey, grey, grey, u8::try_from(a / 2).unwrap_or(u8::MAX)])
+}
+
+/// The colour used to mark differing pixels in a diff.
+const DIFF_COLOUR: Rgba<u8> = Rgba([255, 0, 255, 255]);
+
+/// Errors that can occur when checking snapshots.
+#[derive(Debug, Error)]
+pub enum Error {
+    /// An error occurred loading or saving an image.
+    #[error("image error: {0}")]
+    Image(#[from] image::ImageError),
+
+    /// The image didn't match its reference.
+    #[error("{differing_pixels} pixel(s) differ from {reference:?} (diff at {diff:?})")]
+    Mismatch {
+        reference: PathBuf,
+        differing_pixels: usize,
+        diff: PathBuf,
+    },
+}
+
+/// Shorthand for snapshot results.
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Identical images have no diff.
+    #[test]
+    fn diff_identical() {
+        let image = RgbaImage::from_pixel(3, 2, Rgba([1, 2, 3, 4]));
+        assert!(diff(&image, &image.clone()).is_none());
+    }
+
+    /// Single-pixel differences are counted and marked.
+    #[test]
+    fn diff_one_pixel() {
+        let expected = RgbaImage::new(3, 2);
+        let mut actual = expected.clone();
+        actual.put_pixel(1, 1, Rgba([255, 255, 255, 255]));
+
+        let diff = diff(&actual, &expected).expect("images should differ");
+        assert_eq!(1, diff.differing_pixels);
+        assert_eq!(DIFF_COLOUR, *diff.image.get_pixel(1, 1));
+        assert_ne!(DIFF_COLOUR, *diff.image.get_pixel(0, 0));
+    }
+
+    /// Images of different sizes differ in every pixel outside their overlap.
+    #[test]
+    fn diff_size_mismatch() {
+        let expected = RgbaImage::new(2, 2);
+        let actual = RgbaImage::new(3, 2);
+
+        let diff = diff(&actual, &expected).expect("images should differ");
+        assert_eq!(2, diff.differing_pixels);
+        assert_eq!((3, 2), diff.image.dimensions());
+    }
+}
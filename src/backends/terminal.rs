@@ -0,0 +1,43 @@
+//! A text-mode backend that renders onto a grid of character cells.
+//!
+//! In this backend, every [`crate::metrics::Length`] is a number of cells, and every font has
+//! the same one-cell-per-character [metrics].  Rendering happens into an in-memory [Grid], which
+//! is flushed to any [`std::io::Write`] as ANSI escape sequences on `present`.  Flushing is
+//! incremental: only cells that changed since the last flush are emitted.
+
+mod colour;
+mod grid;
+mod render;
+
+pub use colour::Mode;
+pub use grid::{Cell, Grid};
+pub use render::Renderer;
+
+use crate::font;
+
+/// Constructs the font metrics used for every font in the terminal backend.
+///
+/// Each character is one cell wide and one cell tall, with no padding or kerning.
+///
+/// # Example
+///
+/// ```
+/// let metrics = ugly::backends::terminal::metrics();
+/// assert_eq!(5, metrics.span_w_str("hello"));
+/// ```
+#[must_use]
+pub fn metrics() -> font::Metrics {
+    font::Metrics {
+        char: crate::metrics::Size { w: 1, h: 1 },
+        pad: crate::metrics::Size::default(),
+        chars: font::metrics::chars::Table::uniform(1, 0),
+        atlas: font::metrics::atlas::Table::default(),
+        code_point_rows: 8,
+        baseline: 1,
+        ascent: 1,
+        descent: 0,
+        underline: 0,
+        strikethrough: 0,
+        decoration_thickness: 1,
+    }
+}
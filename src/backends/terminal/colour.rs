@@ -0,0 +1,180 @@
+//! Mapping `ugly` colours onto ANSI terminal colours.
+
+use std::fmt::Write;
+
+use crate::colour::{Definition, EGA};
+
+/// The colour capabilities of a terminal.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Mode {
+    /// The 16 standard ANSI colours (8 dark and 8 bright).
+    ///
+    /// This is the default, as it is the most widely supported mode.
+    #[default]
+    Ansi16,
+    /// The 256-colour `xterm` palette.
+    Ansi256,
+    /// 24-bit true colour.
+    TrueColour,
+}
+
+/// Which layer of a cell a colour applies to.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(super) enum Layer {
+    Foreground,
+    Background,
+}
+
+impl Mode {
+    /// Appends the SGR parameters for setting `layer` to `colour` onto `out`.
+    ///
+    /// A `colour` of `None`, or a transparent colour, resets the layer to the terminal default.
+    pub(super) fn push_sgr(self, out: &mut String, layer: Layer, colour: Option<Definition>) {
+        let Some(colour) = colour.filter(|c| !c.is_transparent()) else {
+            out.push_str(match layer {
+                Layer::Foreground => "39",
+                Layer::Background => "49",
+            });
+            return;
+        };
+
+        let base = match layer {
+            Layer::Foreground => 30,
+            Layer::Background => 40,
+        };
+
+        // Writing to a string can't fail.
+        let _ = match self {
+            Self::Ansi16 => {
+                let (index, bright) = nearest_16(colour);
+                write!(out, "{}", base + index + if bright { 60 } else { 0 })
+            }
+            Self::Ansi256 => write!(out, "{};5;{}", base + 8, nearest_256(colour)),
+            Self::TrueColour => {
+                write!(out, "{};2;{};{};{}", base + 8, colour.r, colour.g, colour.b)
+            }
+        };
+    }
+}
+
+/// The 8 base ANSI colours, in ANSI order, as their dark and bright EGA definitions.
+const ANSI_16: [(Definition, Definition); 8] = [
+    (EGA.dark.black, EGA.bright.black),
+    (EGA.dark.red, EGA.bright.red),
+    (EGA.dark.green, EGA.bright.green),
+    (EGA.dark.yellow, EGA.bright.yellow),
+    (EGA.dark.blue, EGA.bright.blue),
+    (EGA.dark.magenta, EGA.bright.magenta),
+    (EGA.dark.cyan, EGA.bright.cyan),
+    (EGA.dark.white, EGA.bright.white),
+];
+
+/// Finds the nearest of the 16 ANSI colours to `colour`, as an index and brightness flag.
+fn nearest_16(colour: Definition) -> (u8, bool) {
+    (0u8..)
+        .zip(ANSI_16)
+        .flat_map(|(i, (dark, bright))| [((i, false), dark), ((i, true), bright)])
+        .min_by_key(|(_, c)| distance(colour, *c))
+        .map_or((7, false), |(code, _)| code)
+}
+
+/// The channel levels of the 6x6x6 colour cube in the 256-colour palette.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// Finds the nearest entry in the colour cube or greyscale ramp of the 256-colour palette.
+fn nearest_256(colour: Definition) -> u8 {
+    let level = |c: u8| {
+        (0u8..)
+            .zip(CUBE_LEVELS)
+            .min_by_key(|(_, l)| l.abs_diff(c))
+            .map_or(0, |(i, _)| i)
+    };
+    let (r, g, b) = (level(colour.r), level(colour.g), level(colour.b));
+    let cube = Definition::rgb(
+        CUBE_LEVELS[usize::from(r)],
+        CUBE_LEVELS[usize::from(g)],
+        CUBE_LEVELS[usize::from(b)],
+    );
+    let cube_index = 16 + 36 * r + 6 * g + b;
+
+    // The greyscale ramp runs from 8 to 238 in steps of 10.
+    let mean = (u16::from(colour.r) + u16::from(colour.g) + u16::from(colour.b)) / 3;
+    let grey_step = u8::try_from(mean.saturating_sub(3) / 10).map_or(23, |s| s.min(23));
+    let grey_level = 8 + 10 * grey_step;
+    let grey = Definition::rgb(grey_level, grey_level, grey_level);
+
+    if distance(colour, grey) < distance(colour, cube) {
+        232 + grey_step
+    } else {
+        cube_index
+    }
+}
+
+/// Squared Euclidean distance between two colours, ignoring alpha.
+fn distance(x: Definition, y: Definition) -> u32 {
+    let d = |a: u8, b: u8| u32::from(a.abs_diff(b)).pow(2);
+    d(x.r, y.r) + d(x.g, y.g) + d(x.b, y.b)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn sgr(mode: Mode, layer: Layer, colour: Option<Definition>) -> String {
+        let mut out = String::new();
+        mode.push_sgr(&mut out, layer, colour);
+        out
+    }
+
+    /// EGA colours map exactly onto their ANSI 16-colour counterparts.
+    #[test]
+    fn ansi16_ega() {
+        let m = Mode::Ansi16;
+        assert_eq!("31", sgr(m, Layer::Foreground, Some(EGA.dark.red)));
+        assert_eq!("96", sgr(m, Layer::Foreground, Some(EGA.bright.cyan)));
+        assert_eq!("44", sgr(m, Layer::Background, Some(EGA.dark.blue)));
+        assert_eq!("107", sgr(m, Layer::Background, Some(EGA.bright.white)));
+    }
+
+    /// Missing and transparent colours reset to the default.
+    #[test]
+    fn default_colours() {
+        let m = Mode::TrueColour;
+        assert_eq!("39", sgr(m, Layer::Foreground, None));
+        assert_eq!(
+            "49",
+            sgr(
+                m,
+                Layer::Background,
+                Some(crate::colour::definition::TRANSPARENT)
+            )
+        );
+    }
+
+    /// 256-colour mode picks cube entries for saturated colours and the ramp for greys.
+    #[test]
+    fn ansi256() {
+        let m = Mode::Ansi256;
+        assert_eq!(
+            "38;5;196",
+            sgr(m, Layer::Foreground, Some(Definition::rgb(255, 0, 0)))
+        );
+        assert_eq!(
+            "48;5;244",
+            sgr(m, Layer::Background, Some(Definition::rgb(128, 128, 128)))
+        );
+    }
+
+    /// True-colour mode passes colours straight through.
+    #[test]
+    fn true_colour() {
+        assert_eq!(
+            "38;2;12;34;56",
+            sgr(
+                Mode::TrueColour,
+                Layer::Foreground,
+                Some(Definition::rgb(12, 34, 56))
+            )
+        );
+    }
+}
@@ -0,0 +1,114 @@
+//! In-memory character cell grids.
+
+use crate::{colour::Definition, metrics};
+
+/// A character cell.
+///
+/// The default cell is a blank space in the terminal's default colours.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Cell {
+    /// The character in the cell.
+    pub char: char,
+    /// The foreground colour of the cell, or `None` for the terminal default.
+    pub fg: Option<Definition>,
+    /// The background colour of the cell, or `None` for the terminal default.
+    pub bg: Option<Definition>,
+}
+
+impl Default for Cell {
+    fn default() -> Self {
+        Self {
+            char: ' ',
+            fg: None,
+            bg: None,
+        }
+    }
+}
+
+impl Cell {
+    /// Gets whether this cell has the same colours as `other`.
+    #[must_use]
+    pub fn same_style(&self, other: &Self) -> bool {
+        self.fg == other.fg && self.bg == other.bg
+    }
+}
+
+/// A rectangular grid of character cells, stored in row-major order.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Grid {
+    size: metrics::Size,
+    cells: Vec<Cell>,
+}
+
+impl Grid {
+    /// Constructs a blank grid of the given size, in cells.
+    ///
+    /// Negative dimensions are treated as zero.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ugly::{backends::terminal::Grid, metrics::Size};
+    ///
+    /// let grid = Grid::new(Size { w: 4, h: 2 });
+    /// assert_eq!("    ", grid.row_text(1));
+    /// ```
+    #[must_use]
+    pub fn new(size: metrics::Size) -> Self {
+        let size = size.clamp();
+        let len = usize::try_from(size.w * size.h).unwrap_or_default();
+        Self {
+            size,
+            cells: vec![Cell::default(); len],
+        }
+    }
+
+    /// Gets the size of this grid, in cells.
+    #[must_use]
+    pub fn size(&self) -> metrics::Size {
+        self.size
+    }
+
+    /// Gets the cell at `point`, if it is inside the grid.
+    #[must_use]
+    pub fn get(&self, point: metrics::Point) -> Option<&Cell> {
+        self.index(point).map(|i| &self.cells[i])
+    }
+
+    /// Mutably gets the cell at `point`, if it is inside the grid.
+    pub fn get_mut(&mut self, point: metrics::Point) -> Option<&mut Cell> {
+        self.index(point).map(|i| &mut self.cells[i])
+    }
+
+    /// Gets the characters on row `y` as a string.
+    ///
+    /// Rows outside the grid are empty.
+    #[must_use]
+    pub fn row_text(&self, y: metrics::Length) -> String {
+        (0..self.size.w)
+            .filter_map(|x| self.get(metrics::Point { x, y }))
+            .map(|c| c.char)
+            .collect()
+    }
+
+    /// Resets every cell in the grid to `cell`.
+    pub fn fill(&mut self, cell: Cell) {
+        self.cells.fill(cell);
+    }
+
+    /// Iterates over every cell in the grid, along with its position, in row-major order.
+    pub fn iter(&self) -> impl Iterator<Item = (metrics::Point, &Cell)> {
+        let w = self.size.w.max(1);
+        (0..).zip(&self.cells).map(move |(i, cell)| {
+            let point = metrics::Point { x: i % w, y: i / w };
+            (point, cell)
+        })
+    }
+
+    fn index(&self, point: metrics::Point) -> Option<usize> {
+        let in_bounds = (0..self.size.w).contains(&point.x) && (0..self.size.h).contains(&point.y);
+        in_bounds
+            .then(|| usize::try_from(point.y * self.size.w + point.x).ok())
+            .flatten()
+    }
+}
@@ -0,0 +1,302 @@
+//! Rendering onto character cells.
+
+use std::{collections::HashMap, fmt::Write as _, hash::Hash, io};
+
+use crate::{
+    colour,
+    font::{self, Metrics},
+    metrics, resource, Result,
+};
+
+use super::{
+    colour::{Layer, Mode},
+    Cell, Grid,
+};
+
+/// A renderer that draws onto a [Grid] of character cells.
+///
+/// Drawing happens in memory; `present` then flushes any cells that changed since the last
+/// present to `out` as ANSI escape sequences.  Since `present` can't report errors, any I/O error
+/// raised while flushing is stashed, and can be retrieved with [`Renderer::take_error`].
+pub struct Renderer<FontId, Fg, Bg, W> {
+    /// The grid currently being drawn.
+    front: Grid,
+    /// The grid as of the last flush, if any.
+    back: Option<Grid>,
+
+    metrics: resource::DefaultingHashMap<FontId, Metrics>,
+    palette: colour::Palette<Fg, Bg>,
+    mode: Mode,
+
+    out: W,
+    error: Option<io::Error>,
+}
+
+impl<FontId, Fg, Bg, W> crate::ui::layout::LayoutContext<FontId> for Renderer<FontId, Fg, Bg, W>
+where
+    FontId: Copy + Clone + Default + Eq + Hash,
+{
+    fn font_metrics(&self) -> &impl resource::Map<Metrics, Id = FontId> {
+        &self.metrics
+    }
+}
+
+impl<FontId, Fg, Bg, W> crate::Renderer<FontId, Fg::Id, Bg::Id> for Renderer<FontId, Fg, Bg, W>
+where
+    FontId: Copy + Clone + Default + Eq + Hash,
+    Fg: resource::Map<colour::Definition>,
+    Bg: resource::Map<colour::Definition>,
+    W: io::Write,
+{
+    fn write(
+        &mut self,
+        fonts: &font::Chain<FontId>,
+        colour: Fg::Id,
+        str: &font::layout::String,
+        offset: metrics::point::Delta,
+    ) -> Result<()> {
+        let fg = Some(*self.palette.fg.get(colour));
+        let origin = str.bounds.top_left.offset(offset.dx, offset.dy);
+
+        // Later glyphs overwrite earlier ones, so we place glyphs in the order they were laid out.
+        for glyph in str.glyphs.in_order() {
+            let Some(font) = fonts.get(glyph.font) else {
+                continue;
+            };
+            let metrics = resource::Map::get(&self.metrics, font);
+            let Some(char) = metrics.glyph_char(glyph.src.top_left) else {
+                continue;
+            };
+            let point = origin.offset(glyph.dst.dx, glyph.dst.dy);
+            if let Some(cell) = self.front.get_mut(point) {
+                cell.char = char;
+                cell.fg = fg;
+            }
+        }
+
+        // Cells can't hold lines thinner than a character, so decorations aren't drawn.
+
+        Ok(())
+    }
+
+    fn fill(&mut self, rect: metrics::Rect, colour: Bg::Id) -> Result<()> {
+        let bg = *self.palette.bg.get(colour);
+        if bg.is_transparent() {
+            return Ok(());
+        }
+
+        for y in rect.top_left.y..rect.top_left.y + rect.size.h {
+            for x in rect.top_left.x..rect.top_left.x + rect.size.w {
+                if let Some(cell) = self.front.get_mut(metrics::Point { x, y }) {
+                    cell.bg = Some(bg);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn clear(&mut self, colour: Bg::Id) -> Result<()> {
+        let bg = *self.palette.bg.get(colour);
+        self.front.fill(Cell {
+            bg: (!bg.is_transparent()).then_some(bg),
+            ..Cell::default()
+        });
+        Ok(())
+    }
+
+    fn present(&mut self) {
+        if let Err(e) = self.flush() {
+            self.error.get_or_insert(e);
+        }
+    }
+}
+
+impl<FontId, Fg, Bg, W> Renderer<FontId, Fg, Bg, W>
+where
+    FontId: Eq + Hash,
+    W: io::Write,
+{
+    /// Constructs a terminal renderer with a blank grid of `size` cells, flushing to `out`.
+    #[must_use]
+    pub fn new(size: metrics::Size, palette: colour::Palette<Fg, Bg>, mode: Mode, out: W) -> Self {
+        Self {
+            front: Grid::new(size),
+            back: None,
+            metrics: resource::DefaultingHashMap::new(HashMap::new(), super::metrics()),
+            palette,
+            mode,
+            out,
+            error: None,
+        }
+    }
+
+    /// Borrows the grid currently being drawn.
+    #[must_use]
+    pub fn grid(&self) -> &Grid {
+        &self.front
+    }
+
+    /// Borrows the output.
+    #[must_use]
+    pub fn out(&self) -> &W {
+        &self.out
+    }
+
+    /// Mutably borrows the output.
+    ///
+    /// This can be used, for instance, to clear an in-memory output buffer between frames.
+    pub fn out_mut(&mut self) -> &mut W {
+        &mut self.out
+    }
+
+    /// Resizes the grid, discarding its contents and forcing a full redraw on the next flush.
+    pub fn resize(&mut self, size: metrics::Size) {
+        self.front = Grid::new(size);
+        self.back = None;
+    }
+
+    /// Takes the first I/O error raised by `present` since the last call to this function.
+    pub fn take_error(&mut self) -> Option<io::Error> {
+        self.error.take()
+    }
+
+    /// Emits every cell that changed since the last flush to the output.
+    ///
+    /// If there was no last flush, or the grid has been resized since, the whole screen is
+    /// cleared and redrawn.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the output can't be written.
+    pub fn flush(&mut self) -> io::Result<()> {
+        let mut buf = String::new();
+
+        let back = self.back.take().filter(|b| b.size() == self.front.size());
+        if back.is_none() {
+            buf.push_str("\x1b[0m\x1b[2J");
+        }
+
+        // Position and style the terminal is in, if known.
+        let mut cursor: Option<metrics::Point> = None;
+        let mut style: Option<Cell> = None;
+
+        for (point, cell) in self.front.iter() {
+            let unchanged = back.as_ref().and_then(|b| b.get(point)) == Some(cell);
+            if unchanged {
+                continue;
+            }
+
+            if cursor != Some(point) {
+                // Writing to a string can't fail.
+                let _ = write!(buf, "\x1b[{};{}H", point.y + 1, point.x + 1);
+            }
+            if !style.is_some_and(|s| s.same_style(cell)) {
+                self.push_style(&mut buf, cell);
+                style = Some(*cell);
+            }
+            buf.push(cell.char);
+
+            // Printing advances the cursor one cell right.  We never assume it wraps onto the next
+            // row, as terminals disagree on when that happens.
+            cursor = Some(point.offset(1, 0));
+        }
+
+        if style.is_some() {
+            buf.push_str("\x1b[0m");
+        }
+
+        self.back = Some(self.front.clone());
+
+        self.out.write_all(buf.as_bytes())?;
+        self.out.flush()
+    }
+
+    fn push_style(&self, buf: &mut String, cell: &Cell) {
+        buf.push_str("\x1b[");
+        self.mode.push_sgr(buf, Layer::Foreground, cell.fg);
+        buf.push(';');
+        self.mode.push_sgr(buf, Layer::Background, cell.bg);
+        buf.push('m');
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{colour::ega, text::Writer, ui::layout::LayoutContext, Renderer as _};
+
+    type TestRenderer = Renderer<(), colour::Ega, colour::Ega, Vec<u8>>;
+
+    fn renderer() -> TestRenderer {
+        let palette = colour::Palette {
+            fg: colour::EGA,
+            bg: colour::EGA,
+        };
+        Renderer::new(metrics::Size { w: 8, h: 2 }, palette, Mode::Ansi16, vec![])
+    }
+
+    fn write(r: &mut TestRenderer, pos: metrics::Point, str: &str) {
+        let mut writer = Writer::new((), ega::Id::BRIGHT_WHITE);
+        writer.move_to(pos);
+        writer.set_string(str);
+        writer.layout(r.font_metrics());
+        writer.render(r).unwrap();
+    }
+
+    /// Writing places each character of the string in its own cell.
+    #[test]
+    fn write_cells() {
+        let mut r = renderer();
+        write(&mut r, metrics::Point { x: 1, y: 0 }, "hi\nthere");
+
+        assert_eq!(" hi     ", r.grid().row_text(0));
+        assert_eq!(" there  ", r.grid().row_text(1));
+        let cell = r.grid().get(metrics::Point { x: 1, y: 0 }).unwrap();
+        assert_eq!(Some(colour::EGA.bright.white), cell.fg);
+    }
+
+    /// Writing off the edge of the grid clips.
+    #[test]
+    fn write_clipped() {
+        let mut r = renderer();
+        write(&mut r, metrics::Point { x: 5, y: 1 }, "clipped");
+
+        assert_eq!("     cli", r.grid().row_text(1));
+    }
+
+    /// Fills paint backgrounds without touching characters.
+    #[test]
+    fn fill_background() {
+        let mut r = renderer();
+        write(&mut r, metrics::Point::default(), "ab");
+        r.fill(metrics::Rect::new(1, 0, 2, 1), ega::Id::DARK_RED)
+            .unwrap();
+
+        let cell = r.grid().get(metrics::Point { x: 1, y: 0 }).unwrap();
+        assert_eq!('b', cell.char);
+        assert_eq!(Some(colour::EGA.dark.red), cell.bg);
+        let cell = r.grid().get(metrics::Point { x: 0, y: 0 }).unwrap();
+        assert_eq!(None, cell.bg);
+    }
+
+    /// The first flush redraws everything; later flushes only emit changes.
+    #[test]
+    fn flush_diffs() {
+        let mut r = renderer();
+        write(&mut r, metrics::Point::default(), "abc");
+        r.present();
+        let first = String::from_utf8(std::mem::take(r.out_mut())).unwrap();
+        assert!(first.starts_with("\x1b[0m\x1b[2J"));
+        assert!(first.contains("abc"));
+
+        r.present();
+        assert!(r.out().is_empty(), "nothing changed, so nothing to emit");
+
+        write(&mut r, metrics::Point { x: 1, y: 1 }, "z");
+        r.present();
+        let third = String::from_utf8(std::mem::take(r.out_mut())).unwrap();
+        assert_eq!("\x1b[2;2H\x1b[97;49mz\x1b[0m", third);
+        assert!(r.take_error().is_none());
+    }
+}
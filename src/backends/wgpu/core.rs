@@ -1,6 +1,6 @@
 //! The core of the `wgpu` rendering backend.
+use std::rc::Rc;
 use std::sync::Arc;
-use std::{path::Path, rc::Rc};
 use wgpu::{CommandEncoder, RenderPass, TextureView};
 
 use crate::colour;
@@ -33,6 +33,7 @@ impl Core {
     /// # Errors
     ///
     /// Fails if any part of the wgpu bring-up fails.
+    #[allow(clippy::cast_possible_truncation)]
     pub async fn new(window: Arc<winit::window::Window>) -> Result<Self> {
         let instance = wgpu::Instance::default();
         let surface = instance.create_surface(window.clone())?;
@@ -117,12 +118,12 @@ impl Core {
         self.textures.null_texture.clone()
     }
 
-    pub(super) fn load_image(&mut self, path: impl AsRef<Path>) -> Result<Rc<Texture>> {
-        let tex = Texture::load(&self.device, &self.queue, path)?;
+    pub(super) fn load_image(&mut self, image: &image::RgbaImage) -> Rc<Texture> {
+        let tex = Texture::from_image(&self.device, &self.queue, image);
 
         self.textures.register_bind_group(&self.device, &tex);
 
-        Ok(Rc::new(tex))
+        Rc::new(tex)
     }
 
     pub(super) fn render(
@@ -4,9 +4,9 @@
 use super::{instance::Instance, vertex::Vertex, Error, Result};
 
 /// Creates a `wgpu` adapter.
-pub(super) async fn create_adapter<'w>(
+pub(super) async fn create_adapter(
     instance: wgpu::Instance,
-    surface: &wgpu::Surface<'w>,
+    surface: &wgpu::Surface<'_>,
 ) -> Result<wgpu::Adapter> {
     let adapter_options = wgpu::RequestAdapterOptions {
         power_preference: wgpu::PowerPreference::default(),
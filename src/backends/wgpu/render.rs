@@ -39,25 +39,43 @@ where
     Fg: resource::Map<colour::Definition>,
     Bg: resource::Map<colour::Definition>,
 {
-    fn write(&mut self, font: Font::Id, colour: Fg::Id, str: &font::layout::String) -> Result<()> {
+    fn write(
+        &mut self,
+        fonts: &font::Chain<Font::Id>,
+        colour: Fg::Id,
+        str: &font::layout::String,
+        offset: metrics::point::Delta,
+    ) -> Result<()> {
         let colour = self.lookup_fg(colour);
+        let origin = str.bounds.top_left.offset(offset.dx, offset.dy);
 
-        let texture = self
-            .font_manager
-            .data(font, |p| super::font::load(&mut self.core, p))
-            .cloned()?;
+        // Every glyph from the same font in the chain uses the same texture and scale, so we only
+        // look each font up once.
+        let mut resolved: Vec<_> = fonts.iter().map(|&id| (id, None)).collect();
 
         for glyph in &str.glyphs {
+            let Some((id, cached)) = resolved.get_mut(glyph.font) else {
+                continue;
+            };
+            let (texture, font) = if let Some(cached) = cached {
+                cached
+            } else {
+                let texture = self
+                    .font_manager
+                    .data(*id, |p| super::font::load(&mut self.core, p))
+                    .cloned()?;
+                cached.insert((texture, self.font_manager.font(*id).clone()))
+            };
             let material = vertex::Material {
                 texture: texture.clone(),
                 colour,
-                dimensions: glyph.src,
+                dimensions: font.unscale(glyph.src),
             };
 
-            // Assuming that the source and dest are going to be the same
+            // The texture is unscaled, so the quad stretches it to the glyph's size in the metrics.
             let size = glyph.src.size;
             let init_dst = metrics::Rect {
-                top_left: str.bounds.top_left,
+                top_left: origin,
                 size,
             };
 
@@ -74,21 +92,19 @@ where
             self.push_shape(shape);
         }
 
+        for rect in &str.decorations {
+            let top_left = origin.offset(rect.top_left.x, rect.top_left.y);
+            self.push_rect(
+                top_left.to_rect(rect.size, metrics::Anchor::TOP_LEFT),
+                colour,
+            );
+        }
+
         Ok(())
     }
 
     fn fill(&mut self, rect: metrics::Rect, colour: Bg::Id) -> Result<()> {
-        // Make a texture rect whose coordinates will always be negative
-        let tex_rect = metrics::Rect::new(-2, -2, 1, 1);
-
-        let material = vertex::Material {
-            colour: self.lookup_bg(colour),
-            texture: self.core.null_texture(),
-            dimensions: tex_rect,
-        };
-
-        self.push_shape(shape::Shape::quad(rect, material));
-
+        self.push_rect(rect, self.lookup_bg(colour));
         Ok(())
     }
 
@@ -146,6 +162,20 @@ where
         self.shapes.push(shape);
     }
 
+    /// Pushes a solid rectangle of the given colour.
+    fn push_rect(&mut self, rect: metrics::Rect, colour: colour::Definition) {
+        // Make a texture rect whose coordinates will always be negative
+        let tex_rect = metrics::Rect::new(-2, -2, 1, 1);
+
+        let material = vertex::Material {
+            colour,
+            texture: self.core.null_texture(),
+            dimensions: tex_rect,
+        };
+
+        self.push_shape(shape::Shape::quad(rect, material));
+    }
+
     /// Looks up a background colour.
     fn lookup_bg(&self, id: Bg::Id) -> colour::Definition {
         *self.palette.bg.get(id)
@@ -36,6 +36,7 @@ pub(super) struct Manifest {
 
 impl Queue {
     /// Pushes a shape onto the shape queue.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
     pub(super) fn push(&mut self, mut shape: Shape) {
         // TODO: compress similar data (i.e. same instance, same mesh, etc)
         // also compress like shapes into one shape
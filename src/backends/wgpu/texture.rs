@@ -1,5 +1,5 @@
 //! Texture creation and bookkeeping facilities.
-use super::{init, Result};
+use super::init;
 use std::collections::HashMap;
 use std::rc::Rc;
 
@@ -12,24 +12,16 @@ pub struct Texture {
 }
 
 impl Texture {
-    /// Loads an image as a texture.
-    ///
-    /// # Errors
-    ///
-    /// Fails if the image cannot be loaded or decoded.
+    /// Uploads an RGBA image as a texture.
     ///
     /// # Panics
     ///
     /// May panic if something fails at the GPU level.
-    pub(super) fn load(
+    pub(super) fn from_image(
         device: &wgpu::Device,
         queue: &wgpu::Queue,
-        path: impl AsRef<std::path::Path>,
-    ) -> Result<Self> {
-        let reader = image::io::Reader::open(path)?;
-        let image = reader.decode()?;
-        let rgba = image.to_rgba8();
-
+        rgba: &image::RgbaImage,
+    ) -> Self {
         let (width, height) = rgba.dimensions();
 
         let size = wgpu::Extent3d {
@@ -46,7 +38,7 @@ impl Texture {
                 origin: wgpu::Origin3d::ZERO,
                 aspect: wgpu::TextureAspect::All,
             },
-            &rgba,
+            rgba,
             wgpu::ImageDataLayout {
                 offset: 0,
                 bytes_per_row: Some(4 * width),
@@ -55,7 +47,7 @@ impl Texture {
             size,
         );
 
-        Ok(texture)
+        texture
     }
 
     /// Creates a texture on the given device with the given extends and sensible settings.
@@ -40,10 +40,10 @@ impl Vertex {
             screen_xy: [screen_xy.x, screen_xy.y],
             texture_xy: [texture_xy.x, texture_xy.y],
             colour: [
-                colour.r as f32,
-                colour.g as f32,
-                colour.b as f32,
-                colour.a as f32,
+                f32::from(colour.r),
+                f32::from(colour.g),
+                f32::from(colour.b),
+                f32::from(colour.a),
             ],
         }
     }
@@ -44,6 +44,7 @@ where
     }
 
     /// Propagates a rescale to the renderer.
+    #[allow(clippy::cast_possible_truncation)]
     pub fn rescale(&mut self, new_scale: f64) {
         self.on_core(|c| c.rescale(new_scale as f32));
     }
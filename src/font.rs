@@ -1,7 +1,9 @@
 //! Fonts, their metrics, and ways of loading and referring to them.
 //!
-//! The main type is [Font], which refers to a directory filled with font files.  Of these, one
-//! file is a serialisation of the font's [Metrics]; another is its texture (a PNG).
+//! The main type is [Font], which usually refers to a directory filled with font files.  Of these,
+//! one file is a serialisation of the font's [Metrics]; another is its texture (a PNG).  A [Font]
+//! can also refer to a PC Screen Font file, which is imported whenever it is loaded, or to a
+//! [packed](pack) font in memory, which holds both files in one.
 //!
 //! Consumers of `ugly` supply two sets of identifiers that describe a particular font and its
 //! intended foreground colour.  To refer to such a [Font] in the `ugly` system, we have two main
@@ -9,14 +11,22 @@
 //! [Index] represents a cached, resolved index into a rendering backend's own font tables, and is
 //! just a fancy `usize`.  Backends will supply a means for loading [Spec]s (producing [Index]es)
 //! and rendering text using [Index]es.
+//!
+//! Text can be rendered with a [Chain] of fonts, in which case any glyphs missing from the first
+//! font are taken from the next font in the chain that has them.
 
+pub mod analysis;
+pub mod chain;
 pub mod error;
+pub mod import;
 pub mod layout;
 pub mod manager;
 pub mod metrics;
+pub mod pack;
 
-use std::path::PathBuf;
+use std::{borrow::Cow, path::PathBuf};
 
+pub use chain::Chain;
 pub use error::{Error, Result};
 pub use manager::{Index, Manager};
 pub use metrics::Metrics;
@@ -24,43 +34,235 @@ pub use metrics::Metrics;
 /// A font.
 ///
 /// In `ugly`, a font is a directory containing two items: a texture file (PNG), and a metrics file
-/// (RON).
+/// (RON).  Fonts can also come from PC Screen Font (PSF) files, using [`import::psf`], or from
+/// bytes in memory holding a [packed](pack) font.
+///
+/// A font can also refer to any of these rendered at an integer scale, in which case its metrics
+/// are scaled up when loaded.  Backends draw scaled fonts by stretching glyphs from the unscaled
+/// texture, which is shared between fonts that differ only in scale.
 #[derive(Clone, Debug)]
-pub struct Font(PathBuf);
+pub struct Font {
+    /// Where the font's metrics and texture come from.
+    source: Source,
+    /// The integer scale at which to render the font.
+    scale: u8,
+}
+
+/// Places from which fonts can be loaded.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Source {
+    /// A font directory.
+    Dir(PathBuf),
+    /// A PSF1 or PSF2 file.
+    Psf(PathBuf),
+    /// A packed font in memory.
+    Packed(Cow<'static, [u8]>),
+}
 
 impl Font {
     /// Creates a font that refers to the contents of a directory at `path`.
     #[must_use]
     pub fn from_dir(path: impl AsRef<std::path::Path>) -> Self {
-        Self(path.as_ref().to_path_buf())
+        Self::new(Source::Dir(path.as_ref().to_path_buf()))
+    }
+
+    /// Creates a font that refers to the PC Screen Font (PSF1 or PSF2) file at `path`.
+    ///
+    /// The file is imported, and its texture built, each time the font's metrics or texture are
+    /// loaded.
+    #[must_use]
+    pub fn from_psf(path: impl AsRef<std::path::Path>) -> Self {
+        Self::new(Source::Psf(path.as_ref().to_path_buf()))
     }
 
-    /// Constructs the path to the font's texture (a PNG).
+    /// Creates a font from the contents of a packed font, such as one embedded with
+    /// `include_bytes!`.
     ///
-    /// Different backends use the texture in different ways, and it is often easier for them to
-    /// load the file directly rather than take in bytes, so we don't expose high-level 'load
-    /// this texture' functionality here.
+    /// The bytes are only checked when the font's metrics or texture are loaded.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let font = ugly::font::Font::from_packed(b"UGLYFONT...");
+    /// assert!(font.metrics().is_err());
+    /// ```
+    #[must_use]
+    pub fn from_packed(bytes: impl Into<Cow<'static, [u8]>>) -> Self {
+        Self::new(Source::Packed(bytes.into()))
+    }
+
+    fn new(source: Source) -> Self {
+        Self { source, scale: 1 }
+    }
+
+    /// Makes this font render at `scale` times its usual size.
+    ///
+    /// Scaling multiplies every metric of the font, including widths, kerning, and padding, and
+    /// upscales the texture with nearest-neighbour sampling.  A scale of zero is treated as one.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let font = ugly::font::Font::from_dir("test").scaled(2);
+    /// assert_eq!(2, font.scale());
+    /// ```
+    #[must_use]
+    pub fn scaled(self, scale: u8) -> Self {
+        Self {
+            scale: scale.max(1),
+            ..self
+        }
+    }
+
+    /// Gets the integer scale at which this font renders.
+    #[must_use]
+    pub fn scale(&self) -> u8 {
+        self.scale
+    }
+
+    /// Constructs the path to the font's texture (a PNG), if it has one.
+    ///
+    /// This is the unscaled texture.  Fonts imported from other formats, or packed into memory,
+    /// have no texture file.
     ///
     /// # Example
     ///
     /// ```
     /// let path = ugly::font::Font::from_dir("test").texture_path();
-    /// assert_eq!(["test", "font.png"].iter().collect::<std::path::PathBuf>(), path);
+    /// assert_eq!(Some(["test", "font.png"].iter().collect()), path);
+    /// assert_eq!(None, ugly::font::Font::from_psf("test.psf").texture_path());
     /// ```
     #[must_use]
-    pub fn texture_path(&self) -> PathBuf {
-        self.0.join(TEXTURE_FILE)
+    pub fn texture_path(&self) -> Option<PathBuf> {
+        match &self.source {
+            Source::Dir(dir) => Some(dir.join(TEXTURE_FILE)),
+            Source::Psf(_) | Source::Packed(_) => None,
+        }
+    }
+
+    /// Loads the font's texture into an RGBA image, upscaling it to the font's scale.
+    ///
+    /// The upscaled texture lines up with the font's [metrics](Font::metrics), and so is useful
+    /// for analysing glyphs.  Backends instead load the [unscaled
+    /// texture](Font::unscaled_texture) and stretch each glyph as they draw it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the texture file is unreachable or can't be decoded.
+    pub fn texture(&self) -> Result<image::RgbaImage> {
+        let image = self.unscaled_texture()?;
+        if self.scale == 1 {
+            return Ok(image);
+        }
+
+        let scale = u32::from(self.scale);
+        Ok(image::imageops::resize(
+            &image,
+            image.width() * scale,
+            image.height() * scale,
+            image::imageops::FilterType::Nearest,
+        ))
+    }
+
+    /// Loads the font's texture into an RGBA image at its usual size, whatever the font's scale.
+    ///
+    /// Glyph source rectangles from the font's metrics map onto this texture through
+    /// [`Font::unscale`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the texture file is unreachable or can't be decoded.
+    pub fn unscaled_texture(&self) -> Result<image::RgbaImage> {
+        Ok(match &self.source {
+            Source::Dir(dir) => image::open(dir.join(TEXTURE_FILE))
+                .map_err(map_image_err)?
+                .to_rgba8(),
+            Source::Psf(path) => import_psf(path)?.texture,
+            Source::Packed(bytes) => {
+                let (_, png) = pack::unpack(bytes)?;
+                image::load_from_memory_with_format(png, image::ImageFormat::Png)
+                    .map_err(map_image_err)?
+                    .to_rgba8()
+            }
+        })
+    }
+
+    /// Maps `rect`, a glyph source rectangle in this font's metrics, onto its unscaled texture.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ugly::metrics::Rect;
+    ///
+    /// let font = ugly::font::Font::from_dir("test").scaled(2);
+    /// assert_eq!(Rect::new(3, 1, 4, 5), font.unscale(Rect::new(6, 2, 8, 10)));
+    /// ```
+    #[must_use]
+    pub fn unscale(&self, rect: crate::metrics::Rect) -> crate::metrics::Rect {
+        let scale = i32::from(self.scale);
+        crate::metrics::Rect::new(
+            rect.top_left.x / scale,
+            rect.top_left.y / scale,
+            rect.size.w / scale,
+            rect.size.h / scale,
+        )
+    }
+
+    /// Whether this font and `other` load the same unscaled texture.
+    fn shares_texture(&self, other: &Self) -> bool {
+        self.source == other.source
     }
 
     /// Resolves the path to the font's metrics file and tries to load it.
     ///
     /// # Errors
     ///
-    /// Returns an error if the font metrics file is unreachable or unparseable as RON.
+    /// Returns an error if the font metrics file is unreachable or unparseable as RON, or, for
+    /// imported fonts, if the font file is unreachable or ill-formed.
     pub fn metrics(&self) -> Result<Metrics> {
-        let str = std::fs::read_to_string(self.0.join(METRICS_FILE))?;
-        let spec: metrics::Spec = ron::from_str(&str)?;
-        spec.into_metrics()
+        let spec = match &self.source {
+            Source::Dir(dir) => {
+                let str = std::fs::read_to_string(dir.join(METRICS_FILE))?;
+                ron::from_str(&str)?
+            }
+            Source::Psf(path) => import_psf(path)?.spec,
+            Source::Packed(bytes) => ron::from_str(pack::unpack(bytes)?.0)?,
+        };
+        spec.scaled(self.scale).into_metrics()
+    }
+
+    /// Packs this font's metrics and texture into a single [packed](pack) font.
+    ///
+    /// The font is packed at its usual size, whatever its scale.  Font directories are packed
+    /// with their files as they are; imported fonts are packed as they would be saved.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the font's files are unreachable, or, for imported fonts, if the font
+    /// file is ill-formed.
+    pub fn pack(&self) -> Result<Vec<u8>> {
+        match &self.source {
+            Source::Dir(dir) => pack::pack(
+                &std::fs::read_to_string(dir.join(METRICS_FILE))?,
+                &std::fs::read(dir.join(TEXTURE_FILE))?,
+            ),
+            Source::Psf(path) => {
+                let font = import_psf(path)?;
+                pack::pack_spec(&font.spec, &font.texture)
+            }
+            Source::Packed(bytes) => Ok(bytes.to_vec()),
+        }
+    }
+}
+
+fn import_psf(path: &std::path::Path) -> Result<import::Imported> {
+    Ok(import::psf::parse(&std::fs::read(path)?)?)
+}
+
+fn map_image_err(error: image::ImageError) -> Error {
+    match error {
+        image::ImageError::IoError(e) => Error::Io(e),
+        e => Error::TextureLoad(e.to_string()),
     }
 }
 
@@ -70,7 +272,6 @@ pub trait Map: super::resource::Map<Font> {
     type MetricsMap: super::resource::Map<Metrics, Id = Self::Id>;
 
     /// The type of font index maps produced by following this map.
-
     type IndexMap: super::resource::MutableMap<Index, Id = Self::Id> + Default;
 
     /// Loads metrics for all fonts in the map.
@@ -0,0 +1,126 @@
+//! Analysis of glyph shapes in font textures.
+//!
+//! These tools read glyphs back out of a font's texture, using its metrics to find each glyph's
+//! cell, so that hand-written metrics can be generated or checked against the glyphs themselves.
+//!
+//! A pixel counts as ink if it is opaque and light (every channel at least half intensity).
+//! Glyphs are drawn in white or near-white so that they can be tinted, and this skips the
+//! brightly coloured guide marks that font textures often carry for padding and widths.
+
+pub mod kerning;
+pub mod width;
+
+use std::collections::BTreeSet;
+
+use super::{
+    super::metrics::{Length, Point},
+    Metrics,
+};
+
+/// A font texture, paired with the metrics that locate its glyphs.
+#[derive(Clone, Copy, Debug)]
+pub struct Sheet<'a> {
+    metrics: &'a Metrics,
+    texture: &'a image::RgbaImage,
+}
+
+impl<'a> Sheet<'a> {
+    /// Pairs `texture` with the `metrics` that locate its glyphs.
+    ///
+    /// The metrics and texture should be at the same scale.
+    #[must_use]
+    pub fn new(metrics: &'a Metrics, texture: &'a image::RgbaImage) -> Self {
+        Self { metrics, texture }
+    }
+
+    /// Gets the metrics of this sheet.
+    #[must_use]
+    pub fn metrics(&self) -> &'a Metrics {
+        self.metrics
+    }
+
+    /// Gets every character with a glyph on this sheet, in order.
+    #[must_use]
+    pub fn chars(&self) -> BTreeSet<char> {
+        let code_points = u16::from(self.metrics.code_point_rows) * 32;
+        (0..code_points)
+            .filter_map(|c| u8::try_from(c).ok().map(char::from))
+            .chain(self.metrics.atlas.chars())
+            .collect()
+    }
+
+    /// Gets whether the pixel at `x, y` in the glyph for `char` is ink.
+    ///
+    /// Only pixels inside the character box (not the padding) can be ink.
+    #[must_use]
+    pub fn is_ink(&self, char: char, x: Length, y: Length) -> bool {
+        let size = self.metrics.char;
+        if !((0..size.w).contains(&x) && (0..size.h).contains(&y)) {
+            return false;
+        }
+
+        let Point { x: left, y: top } = self.metrics.glyph_top_left(char);
+        let (Ok(x), Ok(y)) = (u32::try_from(left + x), u32::try_from(top + y)) else {
+            return false;
+        };
+        self.texture
+            .get_pixel_checked(x, y)
+            .is_some_and(|p| p.0[3] != 0 && p.0[..3].iter().all(|&c| 128 <= c))
+    }
+
+    /// Gets the width of the inked part of the glyph for `char`: the column just past its
+    /// rightmost ink.
+    ///
+    /// A blank glyph has an ink width of zero.
+    #[must_use]
+    pub fn ink_width(&self, char: char) -> Length {
+        (0..self.metrics.char.w)
+            .rev()
+            .find(|&x| (0..self.metrics.char.h).any(|y| self.is_ink(char, x, y)))
+            .map_or(0, |x| x + 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::metrics::Spec, *};
+    use crate::metrics::Size;
+
+    /// Makes a texture for a 3x2 font with glyphs for '!' and 'Ω' only.
+    pub(super) fn sheet() -> (Metrics, image::RgbaImage) {
+        let metrics = Spec {
+            char: Size { w: 3, h: 2 },
+            pad: Size { w: 1, h: 1 },
+            atlas: [(2, "Ω")].into_iter().collect(),
+            code_point_rows: 2,
+            width_overrides: [("!", 1)].into_iter().collect(),
+            ..Spec::default()
+        }
+        .into_metrics()
+        .expect("should not fail to expand metrics");
+
+        let white = image::Rgba([255, 255, 255, 255]);
+        let mut texture = image::RgbaImage::new(128, 9);
+        // '!' is on row 1, column 1, and is two pixels wide.
+        texture.put_pixel(4, 3, white);
+        texture.put_pixel(5, 4, white);
+        // A width guide, which isn't ink.
+        texture.put_pixel(6, 3, image::Rgba([0, 57, 255, 255]));
+        // 'Ω' is on row 2, column 0.
+        texture.put_pixel(0, 6, white);
+        // Padding, which can't be ink even if it is white.
+        texture.put_pixel(3, 6, white);
+        (metrics, texture)
+    }
+
+    /// Tests measuring ink widths, ignoring guides and padding.
+    #[test]
+    fn ink_width() {
+        let (metrics, texture) = sheet();
+        let sheet = Sheet::new(&metrics, &texture);
+        assert_eq!(2, sheet.ink_width('!'));
+        assert_eq!(1, sheet.ink_width('Ω'));
+        assert_eq!(0, sheet.ink_width('A'));
+        assert_eq!(65, sheet.chars().len());
+    }
+}
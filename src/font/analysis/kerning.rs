@@ -0,0 +1,150 @@
+//! Suggesting kerning pairs from glyph edge profiles.
+//!
+//! By default, every pair of characters is separated by the font's horizontal padding, measured
+//! from the edge of each character's width.  Some pairs have edges shaped so that their ink never
+//! comes that close on any row (a 'T' followed by an 'o', say), and so can be moved closer together
+//! while keeping at least the padding between their ink.  Ink on neighbouring rows counts as close,
+//! so that glyphs don't touch diagonally.
+
+use super::{
+    super::{super::metrics::Length, metrics::kerning},
+    Sheet,
+};
+
+/// Suggests kerning for each pair of glyphs on `sheet` that can be moved closer together.
+///
+/// Pairs are moved no more than `max_tuck` pixels closer than the default spacing.  Pairs with no
+/// ink on the same or neighbouring rows (such as a full stop followed by an apostrophe) can't be
+/// compared, and so get no suggestion.  Blank glyphs, and ink clipped by a glyph's width, are
+/// ignored.
+///
+/// To keep the suggestions reviewable, characters spaced the same way share classes, as when
+/// collecting a [`kerning::Spec`] from pairs.
+#[must_use]
+pub fn suggest(sheet: &Sheet, max_tuck: Length) -> kerning::Spec {
+    let pad = sheet.metrics().pad.w;
+    let profiles: Vec<Profile> = sheet
+        .chars()
+        .into_iter()
+        .map(|c| Profile::new(sheet, c))
+        .filter(|p| !p.is_blank())
+        .collect();
+
+    let pairs = profiles
+        .iter()
+        .flat_map(|l| profiles.iter().map(move |r| (l, r)))
+        .filter_map(|(l, r)| {
+            let spacing = l.spacing(r, pad)?.max(pad - max_tuck);
+            (spacing < pad).then_some((l.char, r.char, spacing))
+        });
+    pairs.collect()
+}
+
+/// The edge profile of a glyph.
+struct Profile {
+    char: char,
+    /// For each row, the number of blank pixels between the left edge and the leftmost ink.
+    left: Vec<Option<Length>>,
+    /// For each row, the number of blank pixels between the rightmost ink and the right edge.
+    right: Vec<Option<Length>>,
+}
+
+impl Profile {
+    fn new(sheet: &Sheet, char: char) -> Self {
+        let width = sheet.metrics().span_w_char(char);
+        let ink = |y| (0..width).filter(move |&x| sheet.is_ink(char, x, y));
+
+        let rows = 0..sheet.metrics().char.h;
+        Self {
+            char,
+            left: rows.clone().map(|y| ink(y).min()).collect(),
+            right: rows.map(|y| ink(y).max().map(|x| width - 1 - x)).collect(),
+        }
+    }
+
+    fn is_blank(&self) -> bool {
+        self.left.iter().all(Option::is_none)
+    }
+
+    /// Calculates the smallest spacing keeping at least `pad` blank pixels between the ink of this
+    /// glyph and that of `right`, on the same or neighbouring rows.
+    ///
+    /// If the glyphs have no ink on the same or neighbouring rows, there is no such spacing.
+    fn spacing(&self, right: &Profile, pad: Length) -> Option<Length> {
+        let gaps = |y: usize| self.right.get(y).copied().flatten();
+        (0..right.left.len())
+            .filter_map(|y| Some((y, right.left[y]?)))
+            .flat_map(|(y, r)| {
+                [y.checked_sub(1), Some(y), Some(y + 1)]
+                    .into_iter()
+                    .filter_map(move |ly| Some(pad - gaps(ly?)? - r))
+            })
+            .max()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::{
+        super::super::metrics::{Metrics, Spec},
+        *,
+    };
+    use crate::metrics::Size;
+
+    /// Makes a 3x3 font with a 'T', and one-pixel-wide ',' and '.' at their bottom left.
+    fn sheet() -> (Metrics, image::RgbaImage) {
+        let metrics = Spec {
+            char: Size { w: 3, h: 3 },
+            pad: Size { w: 1, h: 1 },
+            code_point_rows: 3,
+            width_overrides: [(",.", 1)].into_iter().collect(),
+            ..Spec::default()
+        }
+        .into_metrics()
+        .expect("should not fail to expand metrics");
+
+        let white = image::Rgba([255, 255, 255, 255]);
+        let mut texture = image::RgbaImage::new(128, 12);
+        for (x, y) in [
+            (80, 8),
+            (81, 8),
+            (82, 8),
+            (81, 9),
+            (81, 10),
+            (48, 6),
+            (56, 6),
+        ] {
+            texture.put_pixel(x, y, white);
+        }
+        (metrics, texture)
+    }
+
+    /// Tests that pairs tuck together as far as their profiles allow, with characters spaced the
+    /// same way sharing classes.
+    #[test]
+    fn suggest_tucks() {
+        let (metrics, texture) = sheet();
+        let spec = suggest(&Sheet::new(&metrics, &texture), 2);
+        assert_eq!(vec![",.", "T"], spec.left.keys().collect::<Vec<_>>());
+        assert_eq!(vec![",.", "T"], spec.right.keys().collect::<Vec<_>>());
+        assert_eq!(2, spec.pairs.len());
+        assert_eq!(
+            BTreeMap::from([
+                (',', BTreeMap::from([('T', 0)])),
+                ('.', BTreeMap::from([('T', 0)])),
+                ('T', BTreeMap::from([(',', 0), ('.', 0)])),
+            ]),
+            spec.into_map().expect("should be a valid spec")
+        );
+    }
+
+    /// Tests that suggestions tuck no further than asked.
+    #[test]
+    fn suggest_max_tuck() {
+        let (metrics, texture) = sheet();
+        let spec = suggest(&Sheet::new(&metrics, &texture), 0);
+        assert!(spec.pairs.is_empty());
+    }
+}
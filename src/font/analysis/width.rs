@@ -0,0 +1,84 @@
+//! Inferring and checking width overrides against glyph ink.
+
+use std::collections::BTreeMap;
+
+use super::{
+    super::{super::metrics::Length, metrics::width},
+    Sheet,
+};
+
+/// A glyph whose width cuts off some of its ink.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Clipped {
+    /// The character whose glyph is clipped.
+    pub char: char,
+    /// The width of the character according to the font metrics.
+    pub width: Length,
+    /// The width of the inked part of the glyph.
+    pub ink_width: Length,
+}
+
+/// Infers width overrides for every glyph on `sheet` from the ink of each glyph.
+///
+/// Each glyph's width is its ink width, and only glyphs narrower than the character width get
+/// overrides.  Blank glyphs, such as spaces, have no ink to measure, and so get no overrides.
+#[must_use]
+pub fn infer(sheet: &Sheet) -> width::Spec {
+    let full = sheet.metrics().char.w;
+
+    let mut classes: BTreeMap<Length, String> = BTreeMap::new();
+    for char in sheet.chars() {
+        let width = sheet.ink_width(char);
+        if 0 < width && width < full {
+            classes.entry(width).or_default().push(char);
+        }
+    }
+
+    classes.into_iter().map(|(w, chars)| (chars, w)).collect()
+}
+
+/// Finds every glyph on `sheet` whose width, according to the sheet's metrics, clips its ink.
+///
+/// Clipped ink is never drawn, so these are almost certainly mistakes in the width overrides.
+#[must_use]
+pub fn check(sheet: &Sheet) -> Vec<Clipped> {
+    let metrics = sheet.metrics();
+    sheet
+        .chars()
+        .into_iter()
+        .map(|char| Clipped {
+            char,
+            width: metrics.span_w_char(char),
+            ink_width: sheet.ink_width(char),
+        })
+        .filter(|c| c.width < c.ink_width)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::tests::sheet, *};
+
+    /// Tests inferring overrides from ink.
+    #[test]
+    fn infer_overrides() {
+        let (metrics, texture) = sheet();
+        let spec = infer(&Sheet::new(&metrics, &texture));
+        let map = spec.into_map(3).expect("should be a valid spec");
+        assert_eq!(BTreeMap::from([('!', 2), ('Ω', 1)]), map);
+    }
+
+    /// Tests finding glyphs clipped by their declared width.
+    #[test]
+    fn check_clipped() {
+        let (metrics, texture) = sheet();
+        assert_eq!(
+            vec![Clipped {
+                char: '!',
+                width: 1,
+                ink_width: 2
+            }],
+            check(&Sheet::new(&metrics, &texture))
+        );
+    }
+}
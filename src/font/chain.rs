@@ -0,0 +1,93 @@
+//! Fallback chains of fonts.
+
+/// An ordered chain of fonts, consulted in turn when looking up glyphs.
+///
+/// Every chain has a primary font, which sizes lines and supplies any glyph that no font in the
+/// chain has; it may then have any number of fallbacks, like a CSS `font-family`.  Laid-out glyphs
+/// refer to fonts by their index in the chain, with the primary font at index 0.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Chain<FontId> {
+    /// The fonts in the chain; never empty.
+    fonts: Vec<FontId>,
+}
+
+/// The default chain is the default font, with no fallbacks.
+impl<FontId: Default> Default for Chain<FontId> {
+    fn default() -> Self {
+        Self::new(FontId::default())
+    }
+}
+
+/// Any font is a chain with no fallbacks.
+impl<FontId> From<FontId> for Chain<FontId> {
+    fn from(primary: FontId) -> Self {
+        Self::new(primary)
+    }
+}
+
+impl<FontId> Chain<FontId> {
+    /// Constructs a chain with the given primary font and no fallbacks.
+    #[must_use]
+    pub fn new(primary: FontId) -> Self {
+        Self {
+            fonts: vec![primary],
+        }
+    }
+
+    /// Adds `font` to the end of the chain.
+    #[must_use]
+    pub fn with_fallback(mut self, font: FontId) -> Self {
+        self.fonts.push(font);
+        self
+    }
+
+    /// Replaces the primary font of the chain with `font`, keeping any fallbacks.
+    pub fn set_primary(&mut self, font: FontId) {
+        self.fonts[0] = font;
+    }
+
+    /// Gets the number of fonts in the chain, including the primary font.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.fonts.len()
+    }
+
+    /// Chains are never empty, so this always returns `false`.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// Iterates over the fonts in the chain, starting with the primary font.
+    pub fn iter(&self) -> std::slice::Iter<'_, FontId> {
+        self.fonts.iter()
+    }
+
+    /// Iterates over the fallback fonts in the chain; that is, every font but the primary.
+    pub fn fallbacks(&self) -> std::slice::Iter<'_, FontId> {
+        self.fonts[1..].iter()
+    }
+}
+
+impl<FontId: Copy> Chain<FontId> {
+    /// Gets the primary font of the chain.
+    #[must_use]
+    pub fn primary(&self) -> FontId {
+        self.fonts[0]
+    }
+
+    /// Gets the font at `index` in the chain, if there is one.
+    #[must_use]
+    pub fn get(&self, index: usize) -> Option<FontId> {
+        self.fonts.get(index).copied()
+    }
+}
+
+impl<'a, FontId> IntoIterator for &'a Chain<FontId> {
+    type Item = &'a FontId;
+    type IntoIter = std::slice::Iter<'a, FontId>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
@@ -17,6 +17,31 @@ pub enum Error {
     #[error("Error loading font texture")]
     TextureLoad(String),
 
+    /// An error occurred while writing a metrics file.
+    #[error("Error writing metrics file")]
+    MetricsWrite(#[from] ron::Error),
+
+    /// Error saving a texture file.
+    #[error("Error saving font texture")]
+    TextureSave(String),
+
+    /// An error occurred while reading or writing a packed font.
+    #[error("Error in packed font")]
+    Pack(#[from] super::pack::Error),
+
+    /// An error occurred while importing a BDF font.
+    #[error("Error importing BDF font")]
+    Bdf(#[from] super::import::bdf::Error),
+
+    /// An error occurred while importing a PSF font.
+    #[error("Error importing PSF font")]
+    Psf(#[from] super::import::psf::Error),
+
+    /// An error occurred while baking a `TrueType` or `OpenType` font.
+    #[cfg(feature = "truetype")]
+    #[error("Error baking TrueType font")]
+    TrueType(#[from] super::import::truetype::Error),
+
     /// We tried to use a width override to make a character larger than its bounding box.
     #[error("Can't override a char to be larger than its grid ({grid_width} < {override_width})")]
     OverlyLargeOverride {
@@ -24,6 +49,10 @@ pub enum Error {
         override_width: crate::metrics::Length,
     },
 
+    /// An atlas row had more characters than there are columns in the texture grid.
+    #[error("Atlas row {row} has {len} characters, which is more than the grid allows")]
+    AtlasRowOverflow { row: u16, len: usize },
+
     #[error("Problem compiling kerning tables for font")]
     Kerning(#[from] super::metrics::kerning::Error),
 
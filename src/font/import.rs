@@ -0,0 +1,318 @@
+//! Importing fonts from other formats.
+//!
+//! Importers turn the glyph bitmaps of some other font format into an [Imported] font: a texture
+//! laid out on the usual 32-column grid, and a metrics [Spec] describing it.  This can be saved
+//! as a font directory, and then loaded like any other [Font](super::Font).
+//!
+//! Glyphs for code points up to 255 go on the grid cells matching their code points; any other
+//! glyphs go on atlas rows below those.
+
+pub mod bdf;
+pub mod psf;
+#[cfg(feature = "truetype")]
+pub mod truetype;
+
+use std::{collections::BTreeMap, path::Path};
+
+use super::{
+    super::metrics::{Length, Point, Size},
+    metrics::{self, Spec},
+    Result, METRICS_FILE, TEXTURE_FILE,
+};
+
+/// The largest width or height, in pixels, of the glyphs that importers accept.
+pub const MAX_GLYPH_SIZE: Length = 512;
+
+/// The largest width or height, in pixels, of the textures that importers make.
+pub const MAX_TEXTURE_SIZE: u32 = 16384;
+
+/// Enumeration of possible errors when laying out imported glyphs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum Error {
+    /// The glyph grid would need a texture with a negative size, or one too large to make.
+    #[error("A grid of {rows} rows of {char:?} glyphs doesn't fit on a texture")]
+    TextureSize { char: Size, rows: u32 },
+}
+
+/// A glyph to be imported.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Glyph {
+    /// The character that the glyph represents.
+    pub char: char,
+    /// The width of the glyph, in pixels, not counting padding.
+    pub width: Length,
+    /// The inked pixels of the glyph, relative to the top-left of its cell.
+    ///
+    /// Pixels outside the cell are dropped.
+    pub ink: Vec<Point>,
+}
+
+/// A font imported from another format.
+#[derive(Clone, Debug)]
+pub struct Imported {
+    /// The metrics specification of the font.
+    pub spec: Spec,
+    /// The texture of the font, with white glyphs on a transparent background.
+    pub texture: image::RgbaImage,
+}
+
+impl Imported {
+    /// Lays out `glyphs` onto a texture, using the character size and padding from `spec`.
+    ///
+    /// This fills in the width overrides, code point rows, and atlas of `spec`; everything else
+    /// (such as the baseline) is kept as given.  Glyph widths are clamped to the character width.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the texture would be more than [`MAX_TEXTURE_SIZE`] pixels wide or tall.
+    pub fn from_glyphs(
+        spec: Spec,
+        glyphs: impl IntoIterator<Item = Glyph>,
+    ) -> std::result::Result<Self, Error> {
+        let glyphs: BTreeMap<char, Glyph> = glyphs.into_iter().map(|g| (g.char, g)).collect();
+
+        let code_point_rows = glyphs
+            .keys()
+            .filter_map(|&c| u8::try_from(c).ok())
+            .map(|c| metrics::glyph_row(c) + 1)
+            .max()
+            .unwrap_or(0);
+        let extra: Vec<char> = glyphs
+            .keys()
+            .copied()
+            .filter(|&c| 255 < u32::from(c))
+            .collect();
+        let atlas: Vec<(u16, std::string::String)> = (u16::from(code_point_rows)..)
+            .zip(extra.chunks(usize::from(metrics::NUM_COLS)))
+            .map(|(row, chars)| (row, chars.iter().collect()))
+            .collect();
+
+        let mut widths: BTreeMap<Length, std::string::String> = BTreeMap::new();
+        for g in glyphs.values() {
+            let width = g.width.clamp(0, spec.char.w);
+            if width != spec.char.w {
+                widths.entry(width).or_default().push(g.char);
+            }
+        }
+
+        let spec = Spec {
+            width_overrides: widths.into_iter().map(|(w, chars)| (chars, w)).collect(),
+            atlas: atlas.iter().cloned().collect(),
+            code_point_rows,
+            ..spec
+        };
+        let rows = u32::from(code_point_rows) + u32::try_from(atlas.len()).unwrap_or_default();
+        let texture = draw(&spec, rows, glyphs.values())?;
+        Ok(Self { spec, texture })
+    }
+
+    /// Saves this font as a font directory at `dir`, creating the directory if needed.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the directory or either of its files can't be written.
+    pub fn save(&self, dir: impl AsRef<Path>) -> Result<()> {
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)?;
+
+        let metrics = ron::ser::to_string_pretty(&self.spec, ron::ser::PrettyConfig::default())?;
+        std::fs::write(dir.join(METRICS_FILE), metrics)?;
+
+        self.texture
+            .save(dir.join(TEXTURE_FILE))
+            .map_err(|e| super::Error::TextureSave(e.to_string()))
+    }
+}
+
+/// Gets whether bit `x` of a bitmap row is set, where the most significant bit of each byte is
+/// leftmost.
+fn is_set(row: &[u8], x: Length) -> bool {
+    let Ok(x) = usize::try_from(x) else {
+        return false;
+    };
+    row.get(x / 8)
+        .is_some_and(|byte| byte & (0x80 >> (x % 8)) != 0)
+}
+
+/// Draws `glyphs` onto a texture `rows` grid rows tall, using the grid given by `spec`.
+///
+/// `spec` must already have its atlas filled in.
+fn draw<'a>(
+    spec: &Spec,
+    rows: u32,
+    glyphs: impl Iterator<Item = &'a Glyph>,
+) -> std::result::Result<image::RgbaImage, Error> {
+    let too_large = Error::TextureSize {
+        char: spec.char,
+        rows,
+    };
+    let cell = Size {
+        w: spec.char.w.checked_add(spec.pad.w).ok_or(too_large)?,
+        h: spec.char.h.checked_add(spec.pad.h).ok_or(too_large)?,
+    };
+    let dim = |cells: u32, size: Length| {
+        u32::try_from(size)
+            .ok()
+            .and_then(|size| cells.checked_mul(size))
+            .filter(|&dim| dim <= MAX_TEXTURE_SIZE)
+            .ok_or(too_large)
+    };
+    let mut texture =
+        image::RgbaImage::new(dim(metrics::NUM_COLS.into(), cell.w)?, dim(rows, cell.h)?);
+
+    // Going through full metrics is the easiest way to find each glyph's cell.
+    let Ok(grid) = Spec {
+        char: cell,
+        pad: Size::default(),
+        atlas: spec.atlas.clone(),
+        ..Spec::default()
+    }
+    .into_metrics() else {
+        return Ok(texture);
+    };
+
+    let in_cell = |p: &&Point| (0..spec.char.w).contains(&p.x) && (0..spec.char.h).contains(&p.y);
+    for glyph in glyphs {
+        let top_left = grid.glyph_top_left(glyph.char);
+        for p in glyph.ink.iter().filter(in_cell) {
+            let x = u32::try_from(top_left.x + p.x).unwrap_or_default();
+            let y = u32::try_from(top_left.y + p.y).unwrap_or_default();
+            texture.put_pixel(x, y, image::Rgba([255, 255, 255, 255]));
+        }
+    }
+
+    Ok(texture)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn glyph(char: char, width: Length, ink: &[(Length, Length)]) -> Glyph {
+        Glyph {
+            char,
+            width,
+            ink: ink.iter().map(|&(x, y)| Point { x, y }).collect(),
+        }
+    }
+
+    /// Expands the metrics of an imported `font`.
+    pub(super) fn metrics(font: &Imported) -> crate::font::Metrics {
+        font.spec
+            .clone()
+            .into_metrics()
+            .expect("should not fail to expand metrics")
+    }
+
+    /// A path in the temporary directory, which is removed when dropped.
+    ///
+    /// This cleans up after tests even if their assertions fail.
+    pub(super) struct TempPath(pub(super) std::path::PathBuf);
+
+    impl TempPath {
+        /// Makes a path, unique to this test process, for the file or directory `name`.
+        pub(super) fn new(name: &str) -> Self {
+            let name = format!("ugly-{}-{name}", std::process::id());
+            Self(std::env::temp_dir().join(name))
+        }
+    }
+
+    impl Drop for TempPath {
+        fn drop(&mut self) {
+            // The path may be a file, a directory, or missing if the test failed early.
+            if self.0.is_dir() {
+                let _ = std::fs::remove_dir_all(&self.0);
+            } else {
+                let _ = std::fs::remove_file(&self.0);
+            }
+        }
+    }
+
+    fn imported() -> Imported {
+        let spec = Spec {
+            char: Size { w: 3, h: 4 },
+            pad: Size { w: 1, h: 1 },
+            ..Spec::default()
+        };
+        Imported::from_glyphs(
+            spec,
+            [
+                glyph('A', 3, &[(0, 0), (2, 3)]),
+                glyph('i', 1, &[(0, 1), (5, 0)]),
+                glyph('Ω', 2, &[(1, 1)]),
+                glyph('!', 1, &[]),
+            ],
+        )
+        .expect("should lay out glyphs")
+    }
+
+    /// Tests that glyphs are placed on their code point cells, or on the atlas.
+    #[test]
+    fn from_glyphs_layout() {
+        let font = imported();
+        assert_eq!(4, font.spec.code_point_rows);
+        assert_eq!((128, 25), font.texture.dimensions());
+
+        let metrics = metrics(&font);
+        assert_eq!(Point { x: 0, y: 20 }, metrics.glyph_top_left('Ω'));
+        assert_eq!(1, metrics.span_w_char('i'));
+        assert_eq!(1, metrics.span_w_char('!'));
+        assert_eq!(3, metrics.span_w_char('A'));
+
+        let lit = |x, y| font.texture.get_pixel(x, y).0[3] != 0;
+        assert!(lit(4, 10) && lit(6, 13) && !lit(5, 10));
+        // Ink outside the cell is dropped.
+        assert!(lit(36, 16) && !lit(41, 15));
+        assert!(lit(1, 21));
+    }
+
+    /// Tests that saved fonts load back as font directories.
+    #[test]
+    fn save_round_trip() {
+        let dir = TempPath::new("import");
+        imported().save(&dir.0).expect("should save font");
+
+        let font = super::super::Font::from_dir(&dir.0);
+        let metrics = font.metrics().expect("should load metrics");
+        assert_eq!(1, metrics.span_w_char('i'));
+        assert!(metrics.has_glyph('Ω'));
+        assert_eq!(
+            (128, 25),
+            font.texture().expect("should load texture").dimensions()
+        );
+    }
+
+    /// Tests that glyph grids too large for a texture are rejected.
+    #[test]
+    fn from_glyphs_too_large() {
+        let too_large = |char| {
+            let spec = Spec {
+                char,
+                pad: Size { w: 1, h: 1 },
+                ..Spec::default()
+            };
+            Imported::from_glyphs(spec, [glyph('A', 1, &[])]).err()
+        };
+        assert_eq!(None, too_large(Size { w: 511, h: 511 }));
+        let huge = Size { w: 1000, h: 1 };
+        assert_eq!(
+            Some(Error::TextureSize {
+                char: huge,
+                rows: 3
+            }),
+            too_large(huge)
+        );
+        let overflowing = Size {
+            w: Length::MAX,
+            h: 1,
+        };
+        assert_eq!(
+            Some(Error::TextureSize {
+                char: overflowing,
+                rows: 3
+            }),
+            too_large(overflowing)
+        );
+        assert!(too_large(Size { w: 1, h: -2 }).is_some());
+    }
+}
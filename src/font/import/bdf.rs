@@ -0,0 +1,402 @@
+//! Importing Glyph Bitmap Distribution Format (BDF) fonts.
+//!
+//! BDF glyphs each have their own bounding box, offset from the glyph origin, and an advance
+//! width.  On import, every glyph is placed so that its origin is at the left edge of its cell and
+//! on the font's baseline; padding is zero, so spacing comes entirely from the advance widths.
+//! Glyphs whose ink falls left of the origin are shifted right, and glyphs whose ink falls past the
+//! advance width are widened to fit, so no ink is lost.
+//!
+//! Properties other than the ascent and descent are ignored, as are unencoded glyphs.  Sizes,
+//! offsets, advance widths, and the ascent and descent must all be within [`MAX_GLYPH_SIZE`]
+//! pixels.
+
+use super::{
+    super::{
+        super::metrics::{Length, Point, Size},
+        metrics::Spec,
+    },
+    is_set, Glyph, Imported, MAX_GLYPH_SIZE,
+};
+
+/// Enumeration of possible errors when parsing a BDF font.
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum Error {
+    /// The font has no bounding box.
+    #[error("Missing FONTBOUNDINGBOX")]
+    MissingBoundingBox,
+    /// A line had missing or ill-formed arguments.
+    #[error("Malformed {keyword} on line {line}")]
+    Malformed { line: usize, keyword: String },
+    /// The file ended in the middle of a glyph.
+    #[error("Unterminated glyph {name}")]
+    Unterminated { name: String },
+    /// The glyphs couldn't be laid out onto a texture.
+    #[error("Can't lay out BDF glyphs")]
+    Layout(#[from] super::Error),
+}
+
+/// Shorthand for `Result`s over `Error`.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Imports a font from the contents of a BDF file.
+///
+/// # Errors
+///
+/// Fails if the file is ill-formed.
+///
+/// # Example
+///
+/// ```
+/// let bdf = "STARTFONT 2.1
+/// FONTBOUNDINGBOX 3 2 0 0
+/// CHARS 1
+/// STARTCHAR bar
+/// ENCODING 124
+/// DWIDTH 2 0
+/// BBX 1 2 0 0
+/// BITMAP
+/// 80
+/// 80
+/// ENDCHAR
+/// ENDFONT
+/// ";
+/// let font = ugly::font::import::bdf::parse(bdf).expect("should parse");
+/// assert_eq!(ugly::metrics::Size { w: 2, h: 2 }, font.spec.char);
+/// ```
+pub fn parse(bdf: &str) -> Result<Imported> {
+    let mut lines = bdf.lines().enumerate().map(|(i, l)| Line::new(i + 1, l));
+
+    let mut bounds = None;
+    let mut ascent = None;
+    let mut descent = None;
+    let mut glyphs = vec![];
+
+    while let Some(line) = lines.next() {
+        match line.keyword {
+            "FONTBOUNDINGBOX" => bounds = Some(line.bounding_box()?),
+            "FONT_ASCENT" => ascent = Some(line.length(0, 0)?),
+            "FONT_DESCENT" => descent = Some(line.length(0, 0)?),
+            "STARTCHAR" => {
+                let bounds = bounds.ok_or(Error::MissingBoundingBox)?;
+                glyphs.extend(RawGlyph::parse(&line, &mut lines, bounds)?);
+            }
+            _ => (),
+        }
+    }
+
+    let bounds = bounds.ok_or(Error::MissingBoundingBox)?;
+    let ascent = ascent.unwrap_or(bounds.size.h + bounds.offset.y);
+    let descent = descent.unwrap_or(-bounds.offset.y);
+
+    let glyphs: Vec<Glyph> = glyphs.iter().map(|g| g.to_glyph(ascent)).collect();
+    let spec = Spec {
+        char: Size {
+            w: glyphs.iter().map(|g| g.width).max().unwrap_or_default(),
+            h: ascent + descent,
+        },
+        baseline: Some(ascent),
+        ascent: Some(ascent),
+        descent: Some(descent),
+        ..Spec::default()
+    };
+    Ok(Imported::from_glyphs(spec, glyphs)?)
+}
+
+/// A bounding box, relative to a glyph origin, with y pointing up.
+#[derive(Clone, Copy, Debug, Default)]
+struct BoundingBox {
+    size: Size,
+    offset: Point,
+}
+
+/// A glyph as it appears in a BDF file.
+struct RawGlyph {
+    char: char,
+    advance: Length,
+    bounds: BoundingBox,
+    /// The rows of the bitmap, from top to bottom, as big-endian bytes.
+    rows: Vec<Vec<u8>>,
+}
+
+impl RawGlyph {
+    /// Parses the rest of the glyph started by `start`, using `font_bounds` as a default.
+    ///
+    /// Returns `None` if the glyph has no encoding, or doesn't map to a character.
+    fn parse<'a>(
+        start: &Line,
+        lines: &mut impl Iterator<Item = Line<'a>>,
+        font_bounds: BoundingBox,
+    ) -> Result<Option<Self>> {
+        let unterminated = || Error::Unterminated {
+            name: start.args.join(" "),
+        };
+
+        let mut encoding: Option<i64> = None;
+        let mut advance = None;
+        let mut bounds = font_bounds;
+        let mut rows = vec![];
+
+        loop {
+            let line = lines.next().ok_or_else(unterminated)?;
+            match line.keyword {
+                "ENCODING" => encoding = Some(line.arg(0)?),
+                "DWIDTH" => advance = Some(line.length(0, 0)?),
+                "BBX" => bounds = line.bounding_box()?,
+                "BITMAP" => {
+                    for _ in 0..bounds.size.h {
+                        let line = lines.next().ok_or_else(unterminated)?;
+                        rows.push(line.hex()?);
+                    }
+                }
+                "ENDCHAR" => break,
+                _ => (),
+            }
+        }
+
+        let char = encoding
+            .and_then(|e| u32::try_from(e).ok())
+            .and_then(char::from_u32);
+        Ok(char.map(|char| Self {
+            char,
+            advance: advance.unwrap_or(font_bounds.size.w),
+            bounds,
+            rows,
+        }))
+    }
+
+    /// Converts this glyph into one ready to be imported into a font with the given ascent.
+    fn to_glyph(&self, ascent: Length) -> Glyph {
+        let left = self.bounds.offset.x.max(0);
+        let top = ascent - (self.bounds.size.h + self.bounds.offset.y);
+
+        let ink: Vec<Point> = (0..)
+            .zip(&self.rows)
+            .flat_map(|(y, row)| {
+                (0..self.bounds.size.w)
+                    .filter(|&x| is_set(row, x))
+                    .map(move |x| Point {
+                        x: left + x,
+                        y: top + y,
+                    })
+            })
+            .collect();
+
+        let right = ink.iter().map(|p| p.x + 1).max().unwrap_or_default();
+        Glyph {
+            char: self.char,
+            width: self.advance.max(right),
+            ink,
+        }
+    }
+}
+
+/// A line of a BDF file, split into its keyword and arguments.
+struct Line<'a> {
+    number: usize,
+    keyword: &'a str,
+    args: Vec<&'a str>,
+}
+
+impl<'a> Line<'a> {
+    fn new(number: usize, line: &'a str) -> Self {
+        let mut words = line.split_whitespace();
+        Self {
+            number,
+            keyword: words.next().unwrap_or_default(),
+            args: words.collect(),
+        }
+    }
+
+    fn malformed(&self) -> Error {
+        Error::Malformed {
+            line: self.number,
+            keyword: self.keyword.to_owned(),
+        }
+    }
+
+    /// Parses the argument at `index`.
+    fn arg<T: std::str::FromStr>(&self, index: usize) -> Result<T> {
+        self.args
+            .get(index)
+            .and_then(|a| a.parse().ok())
+            .ok_or_else(|| self.malformed())
+    }
+
+    /// Parses the argument at `index` as a length between `min` and [`MAX_GLYPH_SIZE`].
+    fn length(&self, index: usize, min: Length) -> Result<Length> {
+        let length = self.arg(index)?;
+        if (min..=MAX_GLYPH_SIZE).contains(&length) {
+            Ok(length)
+        } else {
+            Err(self.malformed())
+        }
+    }
+
+    /// Parses the arguments as a bounding box.
+    fn bounding_box(&self) -> Result<BoundingBox> {
+        Ok(BoundingBox {
+            size: Size {
+                w: self.length(0, 0)?,
+                h: self.length(1, 0)?,
+            },
+            offset: Point {
+                x: self.length(2, -MAX_GLYPH_SIZE)?,
+                y: self.length(3, -MAX_GLYPH_SIZE)?,
+            },
+        })
+    }
+
+    /// Parses the keyword as a row of hexadecimal bitmap data.
+    fn hex(&self) -> Result<Vec<u8>> {
+        let digits = self.keyword.as_bytes();
+        digits
+            .chunks(2)
+            .map(|pair| {
+                std::str::from_utf8(pair)
+                    .ok()
+                    .and_then(|s| u8::from_str_radix(s, 16).ok())
+                    .ok_or_else(|| self.malformed())
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::tests::metrics, *};
+
+    const FONT: &str = "STARTFONT 2.1
+FONT -test-
+FONTBOUNDINGBOX 5 6 -1 -1
+STARTPROPERTIES 2
+FONT_ASCENT 5
+FONT_DESCENT 1
+ENDPROPERTIES
+CHARS 3
+STARTCHAR j
+ENCODING 106
+DWIDTH 3 0
+BBX 3 6 -1 -1
+BITMAP
+20
+00
+60
+20
+20
+C0
+ENDCHAR
+STARTCHAR W
+ENCODING 87
+DWIDTH 5 0
+BBX 5 5 0 0
+BITMAP
+88
+88
+A8
+A8
+50
+ENDCHAR
+STARTCHAR unencoded
+ENCODING -1
+BBX 1 1 0 0
+BITMAP
+80
+ENDCHAR
+ENDFONT
+";
+
+    /// Tests the metrics of an imported font.
+    #[test]
+    fn parse_metrics() {
+        let font = parse(FONT).expect("should parse");
+        let metrics = metrics(&font);
+        assert_eq!(Size { w: 5, h: 6 }, metrics.char);
+        assert_eq!(Size::default(), metrics.pad);
+        assert_eq!(
+            (5, 5, 1),
+            (metrics.baseline, metrics.ascent, metrics.descent)
+        );
+        assert_eq!(3, metrics.span_w_char('j'));
+        assert_eq!(5, metrics.span_w_char('W'));
+        assert_eq!(8, metrics.span_w_str("jW"));
+        assert_eq!(4, metrics.code_point_rows);
+    }
+
+    /// Tests that glyph bitmaps land on the baseline, shifted right if they overhang the origin.
+    #[test]
+    fn parse_glyphs() {
+        let font = parse(FONT).expect("should parse");
+        let metrics = metrics(&font);
+        let ink = |c: char| -> Vec<(u32, u32)> {
+            let top_left = metrics.glyph_top_left(c);
+            let x0 = u32::try_from(top_left.x).expect("glyph should be on the texture");
+            let y0 = u32::try_from(top_left.y).expect("glyph should be on the texture");
+            (0..6)
+                .flat_map(|y| (0..5).map(move |x| (x, y)))
+                .filter(|&(x, y)| font.texture.get_pixel(x0 + x, y0 + y).0[3] != 0)
+                .collect()
+        };
+
+        assert_eq!(
+            vec![(2, 0), (1, 2), (2, 2), (2, 3), (2, 4), (0, 5), (1, 5)],
+            ink('j')
+        );
+        assert_eq!(12, ink('W').len());
+        assert!(ink('W').iter().all(|&(_, y)| y < 5));
+    }
+
+    /// Tests that ill-formed files are rejected.
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            Some(Error::MissingBoundingBox),
+            parse("STARTFONT 2.1\n").err()
+        );
+        assert_eq!(
+            Some(Error::Malformed {
+                line: 2,
+                keyword: "FONTBOUNDINGBOX".to_owned()
+            }),
+            parse("STARTFONT 2.1\nFONTBOUNDINGBOX 5 x 0 0\n").err()
+        );
+        assert_eq!(
+            Some(Error::Unterminated {
+                name: "j".to_owned()
+            }),
+            parse(&FONT[..FONT.find("BITMAP").unwrap_or_default()]).err()
+        );
+    }
+
+    /// Tests that sizes, offsets, advance widths, and ascents out of bounds are rejected.
+    #[test]
+    fn parse_bounds_errors() {
+        let malformed = |from: &str, to: &str| {
+            let bdf = FONT.replacen(from, to, 1);
+            let line = bdf.lines().position(|l| l == to).unwrap_or_default() + 1;
+            let keyword = to.split(' ').next().unwrap_or_default().to_owned();
+            assert_eq!(
+                Some(Error::Malformed { line, keyword }),
+                parse(&bdf).err(),
+                "{to} should be rejected"
+            );
+        };
+        malformed("DWIDTH 3 0", "DWIDTH 2000000000 0");
+        malformed("DWIDTH 3 0", "DWIDTH -1 0");
+        malformed("FONT_ASCENT 5", "FONT_ASCENT 2147483647");
+        malformed("FONT_DESCENT 1", "FONT_DESCENT -2147483648");
+        malformed(
+            "FONTBOUNDINGBOX 5 6 -1 -1",
+            "FONTBOUNDINGBOX 100000 6 -1 -1",
+        );
+        malformed("BBX 3 6 -1 -1", "BBX 3 6 -1 -2147483648");
+        malformed("BBX 5 5 0 0", "BBX 5 -5 0 0");
+
+        // The largest glyphs still import.
+        let bdf = FONT
+            .replacen("FONT_ASCENT 5", "FONT_ASCENT 256", 1)
+            .replacen("FONT_DESCENT 1", "FONT_DESCENT 256", 1)
+            .replacen("DWIDTH 5 0", "DWIDTH 512 0", 1);
+        let font = parse(&bdf).expect("should parse");
+        assert_eq!(Size { w: 512, h: 512 }, font.spec.char);
+    }
+}
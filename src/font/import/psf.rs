@@ -0,0 +1,384 @@
+//! Importing PC Screen Font (PSF) console fonts, in both the PSF1 and PSF2 formats.
+//!
+//! Console fonts are monospaced, and have no padding between characters or lines.  If the font has
+//! a Unicode table, each glyph is imported once for each character the table maps onto it
+//! (multi-character sequences are ignored); otherwise, each glyph is imported for the character
+//! whose code point is its index in the font.
+
+use super::{
+    super::{
+        super::metrics::{Length, Point, Size},
+        metrics::Spec,
+    },
+    is_set, Glyph, Imported, MAX_GLYPH_SIZE,
+};
+
+/// Enumeration of possible errors when parsing a PSF font.
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum Error {
+    /// The data doesn't start with either PSF magic number.
+    #[error("Not a PSF font")]
+    BadMagic,
+    /// The data ended before the header, glyphs, or Unicode table said it would.
+    #[error("PSF font is truncated")]
+    Truncated,
+    /// The Unicode table of a PSF2 font contains invalid UTF-8.
+    #[error("PSF font has an ill-formed Unicode table")]
+    BadUnicodeTable,
+    /// The glyphs of a PSF2 font are empty, or larger than [`MAX_GLYPH_SIZE`].
+    #[error("PSF glyphs can't be {0:?}")]
+    BadGlyphSize(Size),
+    /// The glyphs of a PSF2 font have fewer bytes than their size needs.
+    #[error("PSF glyphs of {size:?} need {needed} bytes, not {bytes}")]
+    ShortGlyphs {
+        size: Size,
+        bytes: usize,
+        needed: usize,
+    },
+    /// A PSF2 font has more than [`MAX_GLYPHS`] glyphs.
+    #[error("PSF font has {0} glyphs, which is too many")]
+    TooManyGlyphs(usize),
+    /// The glyphs couldn't be laid out onto a texture.
+    #[error("Can't lay out PSF glyphs")]
+    Layout(#[from] super::Error),
+}
+
+/// Shorthand for `Result`s over `Error`.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Imports a font from the contents of a PSF1 or PSF2 file.
+///
+/// # Errors
+///
+/// Fails if the data isn't a well-formed PSF font.
+pub fn parse(psf: &[u8]) -> Result<Imported> {
+    let font = if psf.starts_with(&PSF1_MAGIC) {
+        Raw::psf1(psf)?
+    } else if psf.starts_with(&PSF2_MAGIC) {
+        Raw::psf2(psf)?
+    } else {
+        return Err(Error::BadMagic);
+    };
+
+    let spec = Spec {
+        char: font.size,
+        ..Spec::default()
+    };
+    Ok(Imported::from_glyphs(spec, font.glyphs())?)
+}
+
+/// The most glyphs that a PSF2 font can have: one for every character in the Basic Multilingual
+/// Plane.
+pub const MAX_GLYPHS: usize = 0x1_0000;
+
+const PSF1_MAGIC: [u8; 2] = [0x36, 0x04];
+const PSF1_MODE512: u8 = 0x01;
+const PSF1_MODEHASTAB: u8 = 0x02;
+const PSF1_MODEHASSEQ: u8 = 0x04;
+const PSF1_SEPARATOR: u16 = 0xFFFF;
+const PSF1_STARTSEQ: u16 = 0xFFFE;
+
+const PSF2_MAGIC: [u8; 4] = [0x72, 0xB5, 0x4A, 0x86];
+const PSF2_HAS_UNICODE_TABLE: u32 = 0x01;
+const PSF2_SEPARATOR: u8 = 0xFF;
+const PSF2_STARTSEQ: u8 = 0xFE;
+
+/// A PSF font, split into its parts.
+struct Raw<'a> {
+    /// The size of each glyph.
+    size: Size,
+    /// The bitmap of each glyph, row by row, with each row padded to a whole number of bytes.
+    bitmaps: Vec<&'a [u8]>,
+    /// The characters mapped onto each glyph, if the font has a Unicode table.
+    chars: Option<Vec<Vec<char>>>,
+}
+
+impl<'a> Raw<'a> {
+    fn psf1(psf: &'a [u8]) -> Result<Self> {
+        let mode = *psf.get(2).ok_or(Error::Truncated)?;
+        let height = *psf.get(3).ok_or(Error::Truncated)?;
+        let count = if mode & PSF1_MODE512 == 0 { 256 } else { 512 };
+
+        let (bitmaps, rest) = bitmaps(psf, 4, count, usize::from(height))?;
+        let chars = (mode & (PSF1_MODEHASTAB | PSF1_MODEHASSEQ) != 0).then(|| {
+            let units: Vec<u16> = rest
+                .chunks_exact(2)
+                .map(|c| u16::from_le_bytes([c[0], c[1]]))
+                .collect();
+            units
+                .split(|&u| u == PSF1_SEPARATOR)
+                .take(count)
+                .map(|entry| {
+                    entry
+                        .iter()
+                        .take_while(|&&u| u != PSF1_STARTSEQ)
+                        .filter_map(|&u| char::from_u32(u.into()))
+                        .collect()
+                })
+                .collect()
+        });
+
+        Ok(Self {
+            size: Size {
+                w: 8,
+                h: height.into(),
+            },
+            bitmaps,
+            chars,
+        })
+    }
+
+    fn psf2(psf: &'a [u8]) -> Result<Self> {
+        let field = |index: usize| -> Result<u32> {
+            let start = 4 + 4 * index;
+            psf.get(start..start + 4)
+                .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                .ok_or(Error::Truncated)
+        };
+        let usize_field = |index| usize::try_from(field(index)?).map_err(|_| Error::Truncated);
+        // Lengths too big to represent are rejected as too big to import, below.
+        let length_field = |index| field(index).map(|f| Length::try_from(f).unwrap_or(Length::MAX));
+
+        let header_size = usize_field(1)?;
+        let flags = field(2)?;
+        let count = usize_field(3)?;
+        let glyph_size = usize_field(4)?;
+        let size = Size {
+            w: length_field(6)?,
+            h: length_field(5)?,
+        };
+
+        if !(1..=MAX_GLYPH_SIZE).contains(&size.w) || !(1..=MAX_GLYPH_SIZE).contains(&size.h) {
+            return Err(Error::BadGlyphSize(size));
+        }
+        // Both dimensions are small and positive, so this can't overflow.
+        let needed = usize::try_from(size.h * ((size.w + 7) / 8)).unwrap_or_default();
+        if glyph_size < needed {
+            return Err(Error::ShortGlyphs {
+                size,
+                bytes: glyph_size,
+                needed,
+            });
+        }
+        if MAX_GLYPHS < count {
+            return Err(Error::TooManyGlyphs(count));
+        }
+
+        let (bitmaps, rest) = bitmaps(psf, header_size, count, glyph_size)?;
+        let chars = if flags & PSF2_HAS_UNICODE_TABLE == 0 {
+            None
+        } else {
+            let entries = rest.split(|&b| b == PSF2_SEPARATOR).take(count);
+            let chars = entries
+                .map(|entry| {
+                    let singles = entry.split(|&b| b == PSF2_STARTSEQ).next().unwrap_or(entry);
+                    std::str::from_utf8(singles)
+                        .map(|s| s.chars().collect())
+                        .map_err(|_| Error::BadUnicodeTable)
+                })
+                .collect::<Result<_>>()?;
+            Some(chars)
+        };
+
+        Ok(Self {
+            size,
+            bitmaps,
+            chars,
+        })
+    }
+
+    /// Converts the glyphs of this font into ones ready to be imported.
+    fn glyphs(&self) -> Vec<Glyph> {
+        let bytes_per_row = usize::try_from((self.size.w + 7) / 8).unwrap_or_default();
+
+        let mut glyphs = vec![];
+        for (index, bitmap) in self.bitmaps.iter().enumerate() {
+            let ink: Vec<Point> = (0..)
+                .zip(bitmap.chunks(bytes_per_row.max(1)))
+                .flat_map(|(y, row)| {
+                    (0..self.size.w)
+                        .filter(|&x| is_set(row, x))
+                        .map(move |x| Point { x, y })
+                })
+                .collect();
+
+            let chars = match &self.chars {
+                Some(chars) => chars.get(index).cloned().unwrap_or_default(),
+                None => u32::try_from(index)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .into_iter()
+                    .collect(),
+            };
+            glyphs.extend(chars.into_iter().map(|char| Glyph {
+                char,
+                width: self.size.w,
+                ink: ink.clone(),
+            }));
+        }
+        glyphs
+    }
+}
+
+/// Splits `count` glyph bitmaps of `size` bytes each from `psf`, starting at `start`.
+///
+/// Also returns the rest of the data after the bitmaps.
+fn bitmaps(psf: &[u8], start: usize, count: usize, size: usize) -> Result<(Vec<&[u8]>, &[u8])> {
+    let end = count
+        .checked_mul(size)
+        .and_then(|len| start.checked_add(len))
+        .ok_or(Error::Truncated)?;
+    let data = psf.get(start..end).ok_or(Error::Truncated)?;
+    let bitmaps = if size == 0 {
+        vec![&[][..]; count]
+    } else {
+        data.chunks(size).collect()
+    };
+    Ok((bitmaps, &psf[end..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::tests::metrics, *};
+
+    /// Makes a PSF1 font whose glyph for index `i` has a single row with the value `i`.
+    fn psf1(table: Option<&[u16]>) -> Vec<u8> {
+        let mode = if table.is_some() { PSF1_MODEHASTAB } else { 0 };
+        let mut psf = vec![PSF1_MAGIC[0], PSF1_MAGIC[1], mode, 1];
+        psf.extend(0..=255);
+        for unit in table.unwrap_or_default() {
+            psf.extend(unit.to_le_bytes());
+        }
+        psf
+    }
+
+    /// Makes a PSF2 header with the given flags, glyph count and size, and glyph dimensions.
+    fn psf2_header(flags: u32, count: u32, glyph_size: u32, w: u32, h: u32) -> Vec<u8> {
+        let mut psf = PSF2_MAGIC.to_vec();
+        for field in [0, 32, flags, count, glyph_size, h, w] {
+            psf.extend(u32::to_le_bytes(field));
+        }
+        psf
+    }
+
+    /// Makes a 10x2 PSF2 font with the given glyph bitmaps and Unicode table.
+    fn psf2(bitmaps: &[[u8; 4]], table: Option<&[u8]>) -> Vec<u8> {
+        let flags = u32::from(table.is_some());
+        let count = u32::try_from(bitmaps.len()).expect("test font should be small");
+        let mut psf = psf2_header(flags, count, 4, 10, 2);
+        psf.extend(bitmaps.iter().flatten());
+        psf.extend(table.unwrap_or_default());
+        psf
+    }
+
+    /// Tests that PSF1 glyphs without a table are mapped by index.
+    #[test]
+    fn parse_psf1() {
+        let font = parse(&psf1(None)).expect("should parse");
+        let metrics = metrics(&font);
+        assert_eq!(Size { w: 8, h: 1 }, metrics.char);
+        assert_eq!(Size::default(), metrics.pad);
+        assert_eq!(8, metrics.code_point_rows);
+
+        // 'A' is 0b0100_0001, on the cell for code point 65.
+        let lit = |x| font.texture.get_pixel(x, 2).0[3] != 0;
+        assert_eq!(vec![9, 15], (8..16).filter(|&x| lit(x)).collect::<Vec<_>>());
+    }
+
+    /// Tests that the PSF1 Unicode table maps glyphs onto characters.
+    #[test]
+    fn parse_psf1_table() {
+        // Glyph 0 is 'Ω' and 'W', glyph 1 is 'x' followed by a sequence, and the rest are
+        // unmapped.
+        let table = [0x3A9, 0x57, 0xFFFF, 0x78, 0xFFFE, 0x41, 0x301, 0xFFFF];
+        let font = parse(&psf1(Some(&table))).expect("should parse");
+        let metrics = metrics(&font);
+        assert!(metrics.has_glyph('Ω'));
+        assert_eq!(4, metrics.code_point_rows);
+
+        // Glyph 1 is on the cell for 'x', and nothing is on the cell for 'A'.
+        let lit = |x, y| font.texture.get_pixel(x, y).0[3] != 0;
+        assert!(lit(199, 3));
+        assert!((8..16).all(|x| !lit(x, 2)));
+    }
+
+    /// Tests PSF2 fonts with wide glyphs and a UTF-8 Unicode table.
+    #[test]
+    fn parse_psf2() {
+        let bitmaps = [[0x80, 0x40, 0x00, 0x00], [0x00, 0x00, 0xFF, 0xC0]];
+        let table = [
+            "é".as_bytes(),
+            &[0xFF],
+            "Ωa".as_bytes(),
+            &[0xFE],
+            b"b",
+            &[0xFF],
+        ]
+        .concat();
+        let font = parse(&psf2(&bitmaps, Some(&table))).expect("should parse");
+        let metrics = metrics(&font);
+        assert_eq!(Size { w: 10, h: 2 }, metrics.char);
+        assert_eq!(10, metrics.span_w_char('é'));
+
+        let lit = |c, x, y| {
+            let top_left = metrics.glyph_top_left(c);
+            let x = u32::try_from(top_left.x + x).expect("should be on texture");
+            let y = u32::try_from(top_left.y + y).expect("should be on texture");
+            font.texture.get_pixel(x, y).0[3] != 0
+        };
+        assert!(lit('é', 0, 0) && !lit('é', 1, 0));
+        assert!(lit('Ω', 9, 1) && lit('a', 9, 1) && !lit('Ω', 9, 0));
+        // 'b' only appears in a sequence.
+        assert!(!lit('b', 0, 1));
+    }
+
+    /// Tests loading PSF files directly as fonts, including at a scale.
+    #[test]
+    fn font_from_psf() {
+        let path = super::super::tests::TempPath::new("font.psf");
+        std::fs::write(&path.0, psf1(None)).expect("should write font");
+
+        let font = crate::Font::from_psf(&path.0).scaled(2);
+        let metrics = font.metrics().expect("should load metrics");
+        assert_eq!(Size { w: 16, h: 2 }, metrics.char);
+        assert_eq!(
+            (512, 16),
+            font.texture().expect("should load texture").dimensions()
+        );
+    }
+
+    /// Tests that ill-formed fonts, and fonts with glyphs too small, large, or many, are rejected.
+    #[test]
+    fn parse_errors() {
+        assert_eq!(Some(Error::BadMagic), parse(b"STARTFONT").err());
+        assert_eq!(Some(Error::Truncated), parse(&psf1(None)[..100]).err());
+        assert_eq!(Some(Error::Truncated), parse(&PSF2_MAGIC).err());
+        assert_eq!(
+            Some(Error::BadUnicodeTable),
+            parse(&psf2(&[[0; 4]], Some(&[0xC3, 0xFF]))).err()
+        );
+
+        // PSF2 headers with glyphs too small, too large, or too many.
+        let err = |count, glyph_size, w, h| parse(&psf2_header(0, count, glyph_size, w, h)).err();
+        let bad_size = |w, h| Some(Error::BadGlyphSize(Size { w, h }));
+        assert_eq!(bad_size(0x7FFF_FFFF, 2), err(1, 4, 0x7FFF_FFFF, 2));
+        assert_eq!(bad_size(Length::MAX, 2), err(1, 4, u32::MAX, 2));
+        assert_eq!(bad_size(100_000_000, 1), err(1, 12_500_000, 100_000_000, 1));
+        assert_eq!(bad_size(0, 2), err(1, 4, 0, 2));
+        assert_eq!(bad_size(8, 0), err(1, 0, 8, 0));
+        assert_eq!(
+            Some(Error::ShortGlyphs {
+                size: Size { w: 10, h: 2 },
+                bytes: 0,
+                needed: 4
+            }),
+            err(u32::MAX, 0, 10, 2)
+        );
+        assert_eq!(
+            Some(Error::TooManyGlyphs(0xFFFF_FFFF)),
+            err(u32::MAX, 4, 10, 2)
+        );
+        // A glyph count within bounds, but beyond the end of the data, is still truncation.
+        assert_eq!(Some(Error::Truncated), err(0x1_0000, 4, 10, 2));
+    }
+}
@@ -0,0 +1,224 @@
+//! Baking `TrueType` and `OpenType` fonts into pixel fonts at a fixed size.
+//!
+//! Baking rasterises each glyph outline without antialiasing: a pixel is inked if the outline
+//! covers at least half of it.  Each glyph is placed so that its origin is at the left edge of its
+//! cell and on the font's baseline, and takes its width from its rounded advance width; padding is
+//! zero, and kerning pairs come from the font's `kern` table.  As with BDF fonts, glyphs whose ink
+//! falls left of the origin are shifted right, and glyphs whose ink falls past the advance width
+//! are widened to fit.
+//!
+//! This needs the `truetype` feature.
+
+use ab_glyph::{Font as _, ScaleFont as _};
+
+use super::{
+    super::{
+        super::metrics::{Length, Point, Size},
+        metrics::{kerning, Spec},
+    },
+    Glyph, Imported,
+};
+
+/// Enumeration of possible errors when baking a font.
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum Error {
+    /// The data isn't a font that can be parsed.
+    #[error("Not a valid TrueType or OpenType font")]
+    InvalidFont,
+    /// The pixel size is zero or negative, or the font has no way of scaling to it.
+    #[error("Can't bake font at {0} pixels")]
+    BadSize(Length),
+    /// The glyphs couldn't be laid out onto a texture.
+    #[error("Can't lay out baked glyphs")]
+    Layout(#[from] super::Error),
+}
+
+/// Shorthand for `Result`s over `Error`.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// The characters baked by default: the printable characters of ISO 8859-1.
+pub fn latin1() -> impl Iterator<Item = char> {
+    (' '..='~').chain('\u{A0}'..='ÿ')
+}
+
+/// Bakes the contents of a `TrueType` or `OpenType` file at `size` pixels per em.
+///
+/// Only the characters in `chars` are baked, and any of those that the font lacks are skipped.
+///
+/// # Errors
+///
+/// Fails if the data isn't a well-formed font, or `size` isn't positive.
+pub fn bake(ttf: &[u8], size: Length, chars: impl IntoIterator<Item = char>) -> Result<Imported> {
+    let font = ab_glyph::FontRef::try_from_slice(ttf).map_err(|_| Error::InvalidFont)?;
+    // Scales in ab_glyph are relative to the font's height, not its em.
+    let scale = u16::try_from(size)
+        .ok()
+        .filter(|&s| 0 < s)
+        .zip(font.units_per_em())
+        .map(|(s, em)| ab_glyph::PxScale::from(f32::from(s) * font.height_unscaled() / em))
+        .ok_or(Error::BadSize(size))?;
+    let font = font.as_scaled(scale);
+
+    let top = font.ascent().ceil();
+    let ascent = to_length(top);
+    let descent = to_length((-font.descent()).ceil());
+
+    let mut ids: Vec<(char, ab_glyph::GlyphId)> = chars
+        .into_iter()
+        .map(|c| (c, font.glyph_id(c)))
+        .filter(|(_, id)| id.0 != 0)
+        .collect();
+    ids.sort_unstable();
+    ids.dedup();
+
+    let glyphs: Vec<Glyph> = ids
+        .iter()
+        .map(|&(char, id)| rasterise(&font, char, id, top))
+        .collect();
+
+    let kerning: kerning::Spec = ids
+        .iter()
+        .flat_map(|&(l, lid)| ids.iter().map(move |&(r, rid)| (l, r, font.kern(lid, rid))))
+        .map(|(l, r, kern)| (l, r, to_length(kern.round())))
+        .filter(|&(_, _, kern)| kern != 0)
+        .collect();
+
+    let spec = Spec {
+        char: Size {
+            w: glyphs.iter().map(|g| g.width).max().unwrap_or_default(),
+            h: ascent + descent,
+        },
+        kerning,
+        baseline: Some(ascent),
+        ascent: Some(ascent),
+        descent: Some(descent),
+        ..Spec::default()
+    };
+    Ok(Imported::from_glyphs(spec, glyphs)?)
+}
+
+/// Rasterises the glyph `id`, for `char`, with its origin `ascent` pixels down from the top.
+fn rasterise<F: ab_glyph::Font>(
+    font: &ab_glyph::PxScaleFont<F>,
+    char: char,
+    id: ab_glyph::GlyphId,
+    ascent: f32,
+) -> Glyph {
+    let advance = to_length(font.h_advance(id).round());
+    let glyph = id.with_scale_and_position(font.scale(), ab_glyph::point(0.0, ascent));
+
+    let mut ink = vec![];
+    if let Some(outline) = font.outline_glyph(glyph) {
+        let bounds = outline.px_bounds();
+        let (left, top) = (to_length(bounds.min.x), to_length(bounds.min.y));
+        outline.draw(|x, y, coverage| {
+            if 0.5 <= coverage {
+                ink.push(Point {
+                    x: left + Length::try_from(x).unwrap_or_default(),
+                    y: top + Length::try_from(y).unwrap_or_default(),
+                });
+            }
+        });
+    }
+
+    // Shift any ink left of the origin back onto the cell.
+    let shift = ink.iter().map(|p| p.x).min().unwrap_or_default().min(0);
+    for p in &mut ink {
+        p.x -= shift;
+    }
+
+    let right = ink.iter().map(|p| p.x + 1).max().unwrap_or_default();
+    Glyph {
+        char,
+        width: advance.max(right),
+        ink,
+    }
+}
+
+/// Converts an already-rounded pixel measurement into a length.
+#[allow(clippy::cast_possible_truncation)]
+fn to_length(x: f32) -> Length {
+    x as Length
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::tests::metrics, *};
+
+    /// A tiny font of blocky glyphs, with 1000 units per em and kerning; see `tests/fonts`.
+    const BLOCKS: &[u8] = include_bytes!("../../../tests/fonts/blocks.ttf");
+
+    /// Bakes the test font so that each pixel is 100 font units.
+    fn blocks() -> Imported {
+        bake(BLOCKS, 10, latin1()).expect("should bake")
+    }
+
+    /// Tests the metrics of a baked font, including its widths and kerning pairs.
+    #[test]
+    fn bake_metrics() {
+        let font = blocks();
+        let metrics = metrics(&font);
+        assert_eq!(Size { w: 7, h: 10 }, metrics.char);
+        assert_eq!(Size::default(), metrics.pad);
+        assert_eq!(
+            (8, 8, 2),
+            (metrics.baseline, metrics.ascent, metrics.descent)
+        );
+
+        let widths: Vec<_> = "ILT._".chars().map(|c| metrics.span_w_char(c)).collect();
+        assert_eq!(vec![4, 7, 6, 3, 5], widths);
+        assert_eq!(3, metrics.code_point_rows);
+
+        let pairs: Vec<_> = font
+            .spec
+            .kerning
+            .pairs
+            .iter()
+            .map(|((l, r), k)| (l.as_str(), r.as_str(), *k))
+            .collect();
+        assert_eq!(vec![("L", "T", -2), ("T", ".", -1)], pairs);
+        assert_eq!(11, metrics.span_w_str("LT"));
+        assert_eq!(13, metrics.span_w_str("TL"));
+    }
+
+    /// Tests that baked glyphs are inked where their outlines cover, relative to the baseline.
+    #[test]
+    fn bake_glyphs() {
+        let font = blocks();
+        let metrics = metrics(&font);
+        let lit = |c: char, x, y| {
+            let top_left = metrics.glyph_top_left(c);
+            let x = u32::try_from(top_left.x + x).expect("should be on texture");
+            let y = u32::try_from(top_left.y + y).expect("should be on texture");
+            font.texture.get_pixel(x, y).0[3] != 0
+        };
+
+        // 'I' has a one-pixel left bearing, and sits on the baseline.
+        assert!(lit('I', 1, 0) && lit('I', 2, 7) && !lit('I', 0, 0) && !lit('I', 3, 0));
+        assert!(!lit('I', 1, 8));
+        // 'L' has its foot, and 'T' its bar, but not the other way round.
+        assert!(lit('L', 5, 7) && !lit('L', 5, 0));
+        assert!(lit('T', 5, 0) && !lit('T', 5, 7) && lit('T', 2, 7));
+        // Characters missing from the font are skipped.
+        assert!((0..7).all(|x| (0..10).all(|y| !lit('A', x, y))));
+        // '_' is a pixel below the baseline.
+        assert!(lit('_', 0, 9) && lit('_', 4, 9) && !lit('_', 0, 8) && !lit('_', 5, 9));
+    }
+
+    /// Tests that data that isn't a font is rejected.
+    #[test]
+    fn bake_invalid() {
+        assert_eq!(
+            Some(Error::InvalidFont),
+            bake(b"not a font", 12, latin1()).err()
+        );
+    }
+
+    /// Tests that the default character set covers printable Latin-1.
+    #[test]
+    fn latin1_chars() {
+        let chars: Vec<char> = latin1().collect();
+        assert_eq!(95 + 96, chars.len());
+        assert!(chars.contains(&'é') && !chars.contains(&'\u{7F}'));
+    }
+}
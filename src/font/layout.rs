@@ -1,9 +1,16 @@
 //! Layout algorithm for strings.
 
+pub mod caret;
+pub mod decoration;
+pub mod tab;
+
+pub use decoration::Decorations;
+pub use tab::{TabStop, TabStops};
+
 use std::collections::HashMap;
 
 use super::{
-    super::metrics::{anchor, point, Length, Rect, Size},
+    super::metrics::{anchor, point, Length, Point, Rect, Size},
     metrics::chars,
     Metrics,
 };
@@ -17,28 +24,103 @@ pub struct String {
     pub string: std::string::String,
     /// The bounding box.
     pub bounds: Rect,
+    /// The distance from the top of the bounding box to the baseline of the first line.
+    pub baseline: Length,
     /// The positions of each glyph.
     pub glyphs: GlyphSet,
+    /// The rectangles of any decorations, relative to the top-left of the bounds.
+    ///
+    /// Renderers fill these in the same colour as the glyphs.
+    pub decorations: Vec<Rect>,
 }
 
 /// The set of glyph positions (source and destination) making up a string.
+///
+/// Glyphs can be viewed in two ways.  Iterating over a `&GlyphSet` groups glyphs by the index of
+/// their font in the font chain and their source, in order of each source's first appearance; this
+/// suits instanced drawing.  [`GlyphSet::in_order`] instead yields each glyph individually, in the
+/// order it was laid out; this suits drawing where overlapping glyphs need a stable order.
+/// Both views are deterministic.
 #[derive(Default, Debug, Clone, Eq, PartialEq)]
 pub struct GlyphSet {
-    glyphs: HashMap<Rect, Vec<point::Delta>>,
+    /// Each distinct glyph source, in order of first appearance, with all of its destinations.
+    sources: Vec<Source>,
+    /// Maps each font index and source rectangle to its position in `sources`.
+    lookup: HashMap<(usize, Rect), usize>,
+    /// Every glyph in layout order, as a position in `sources` and then in its destinations.
+    order: Vec<(usize, usize)>,
+    /// The box of each laid-out character, in layout order.
+    chars: Vec<CharBox>,
+}
+
+/// A glyph source, with every destination at which it appears.
+#[derive(Debug, Clone, Eq, PartialEq)]
+struct Source {
+    font: usize,
+    src: Rect,
+    dsts: Vec<point::Delta>,
+}
+
+/// The box occupied by a laid-out character, used for hit-testing and carets.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+struct CharBox {
+    /// The index of the character in the laid-out string, in characters.
+    index: usize,
+    /// The box, relative to the top-left of the laid-out string.
+    ///
+    /// The box spans the character's width and the height of a line in the primary font.
+    rect: Rect,
 }
 
 impl GlyphSet {
     fn iter(&self) -> GlyphIter<'_> {
-        self.glyphs
-            .iter()
-            .map(|(src, dsts)| Glyph { src: *src, dsts })
+        GlyphIter(self.sources.iter())
+    }
+
+    /// Iterates over every glyph individually, in the order in which they were laid out.
+    pub fn in_order(&self) -> impl Iterator<Item = Placement> + '_ {
+        self.order.iter().map(|&(source, dst)| {
+            let source = &self.sources[source];
+            Placement {
+                font: source.font,
+                src: source.src,
+                dst: source.dsts[dst],
+            }
+        })
+    }
+
+    /// Gets whether there are no glyphs in this set.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+
+    /// Gets the total number of glyphs in this set.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.order.len()
     }
 }
 
-type GlyphIter<'a> = std::iter::Map<
-    std::collections::hash_map::Iter<'a, Rect, Vec<point::Delta>>,
-    fn((&'a Rect, &'a Vec<point::Delta>)) -> Glyph<'a>,
->;
+/// Iterator over the glyphs of a [`GlyphSet`], grouped by source.
+#[derive(Clone, Debug)]
+pub struct GlyphIter<'a>(std::slice::Iter<'a, Source>);
+
+impl<'a> Iterator for GlyphIter<'a> {
+    type Item = Glyph<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|s| Glyph {
+            font: s.font,
+            src: s.src,
+            dsts: &s.dsts,
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
 
 impl<'a> IntoIterator for &'a GlyphSet {
     type Item = Glyph<'a>;
@@ -50,20 +132,26 @@ impl<'a> IntoIterator for &'a GlyphSet {
 }
 
 impl GlyphSet {
-    fn push(&mut self, src: Rect, delta: point::Delta) {
-        let dsts = self.glyphs.entry(src).or_default();
-        dsts.push(delta);
-    }
+    fn push(&mut self, font: usize, src: Rect, delta: point::Delta) {
+        let source = *self.lookup.entry((font, src)).or_insert_with(|| {
+            self.sources.push(Source {
+                font,
+                src,
+                dsts: vec![],
+            });
+            self.sources.len() - 1
+        });
 
-    fn extend(&mut self, src: Rect, deltas: impl IntoIterator<Item = point::Delta>) {
-        let dsts = self.glyphs.entry(src).or_default();
-        dsts.extend(deltas);
+        let dsts = &mut self.sources[source].dsts;
+        self.order.push((source, dsts.len()));
+        dsts.push(delta);
     }
 
     fn merge(&mut self, other: GlyphSet) {
-        for (src, dsts) in other.glyphs {
-            self.extend(src, dsts);
+        for glyph in other.in_order() {
+            self.push(glyph.font, glyph.src, glyph.dst);
         }
+        self.chars.extend(other.chars);
     }
 
     fn realign(&mut self, alignment: anchor::X, line_width: Length, total_width: Length) {
@@ -80,41 +168,261 @@ impl GlyphSet {
             return;
         }
 
-        for delta in self.glyphs.values_mut().flat_map(|g| g.iter_mut()) {
+        for delta in self.sources.iter_mut().flat_map(|s| s.dsts.iter_mut()) {
             delta.dx += dw;
         }
+        for char in &mut self.chars {
+            char.rect.top_left.x += dw;
+        }
+    }
+
+    /// Spreads `extra` pixels of space across the word gaps starting at each of `gaps`.
+    fn justify(&mut self, gaps: &[Length], extra: Length) {
+        let count = Length::try_from(gaps.len()).unwrap_or(Length::MAX);
+
+        // The space given to all gaps left of `x`.  Spreading it proportionally hands out any
+        // remainder evenly across the line.
+        let shift = |x: Length| {
+            let before = gaps.iter().fold(0, |n, &g| n + Length::from(g < x));
+            extra * before / count
+        };
+
+        for delta in self.sources.iter_mut().flat_map(|s| s.dsts.iter_mut()) {
+            delta.dx += shift(delta.dx);
+        }
+        for char in &mut self.chars {
+            let left = char.rect.top_left.x;
+            // The space starting each gap widens to cover the gap, for the sake of selections.
+            char.rect.size.w += shift(left + 1) - shift(left);
+            char.rect.top_left.x += shift(left);
+        }
+    }
+}
+
+/// How to align each line of a layout within the bounds of the layout.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum LineAlignment {
+    /// Align every line to the given anchor.
+    Anchor(anchor::X),
+    /// Justify every line that was wrapped, by spreading extra space across its word gaps so that
+    /// it fills the maximum width of the layout.
+    ///
+    /// Lines that end a paragraph, or that have no word gaps, are aligned to the given anchor.
+    Justify(anchor::X),
+}
+
+/// Default line alignment is to the left.
+impl Default for LineAlignment {
+    fn default() -> Self {
+        Self::Anchor(anchor::X::default())
+    }
+}
+
+/// Any anchor can align lines.
+impl From<anchor::X> for LineAlignment {
+    fn from(anchor: anchor::X) -> Self {
+        Self::Anchor(anchor)
+    }
+}
+
+/// How to lay out words too long to fit within the maximum width of a layout.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub enum LongWords {
+    /// Break long words mid-word, wrapping onto as many lines as needed.
+    Break,
+    /// Let long words overflow the maximum width.
+    #[default]
+    Overflow,
+}
+
+/// The distance from the top of each line of a layout to the top of the next.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub enum LineHeight {
+    /// The padded height of the primary font.
+    #[default]
+    Font,
+    /// A fixed number of pixels.
+    Pixels(Length),
+    /// A percentage of the padded height of the primary font, rounded down.
+    Percent(u16),
+}
+
+impl LineHeight {
+    /// Calculates this line height for a layout whose primary font has metrics `metrics`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ugly::font::layout::LineHeight;
+    ///
+    /// let metrics = ugly::backends::terminal::metrics();
+    /// assert_eq!(1, LineHeight::Font.resolve(&metrics));
+    /// assert_eq!(3, LineHeight::Pixels(3).resolve(&metrics));
+    /// assert_eq!(2, LineHeight::Percent(250).resolve(&metrics));
+    /// ```
+    #[must_use]
+    pub fn resolve(self, metrics: &Metrics) -> Length {
+        match self {
+            Self::Font => metrics.padded_h(),
+            Self::Pixels(height) => height,
+            Self::Percent(percent) => metrics.padded_h() * Length::from(percent) / 100,
+        }
+    }
+}
+
+/// Where to cut lines that are too wide to fit within the maximum width of a layout.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub enum TruncateAt {
+    /// Cut characters from the start of the line.
+    Start,
+    /// Cut characters from the middle of the line.
+    Middle,
+    /// Cut characters from the end of the line.
+    #[default]
+    End,
+}
+
+impl TruncateAt {
+    /// Cuts `chars` down to `keep` characters, putting `ellipsis` in place of the cut.
+    ///
+    /// The ellipsis takes the span and index of the first character cut.  Any spaces next to the
+    /// cut are also removed.
+    fn cut(self, chars: &[SpanChar], ellipsis: &str, keep: usize) -> Vec<SpanChar> {
+        let (head, tail) = match self {
+            Self::Start => (0, keep),
+            Self::Middle => (keep.div_ceil(2), keep / 2),
+            Self::End => (keep, 0),
+        };
+        let SpanChar { span, index, .. } = chars[head];
+        let ellipsis: Vec<SpanChar> = ellipsis
+            .chars()
+            .map(|char| SpanChar { span, index, char })
+            .collect();
+
+        let head = &chars[..head];
+        let tail = &chars[chars.len() - tail..];
+
+        let head_len = head.len()
+            - head
+                .iter()
+                .rev()
+                .take_while(|c| c.char.is_whitespace())
+                .count();
+        let tail_start = tail.iter().take_while(|c| c.char.is_whitespace()).count();
+
+        [&head[..head_len], &ellipsis, &tail[tail_start..]].concat()
+    }
+}
+
+/// Settings for truncating lines that are too wide to fit within the maximum width of a layout.
+///
+/// The default truncation cuts characters from the end of the line, replacing them with `...`.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Truncation {
+    /// Where to cut the line.
+    pub at: TruncateAt,
+    /// The text that replaces the characters cut from the line, such as `...` or `…`.
+    pub ellipsis: std::string::String,
+}
+
+impl Default for Truncation {
+    fn default() -> Self {
+        Self {
+            at: TruncateAt::default(),
+            ellipsis: "...".to_owned(),
+        }
+    }
+}
+
+/// A span of text to be laid out with its own chain of fonts.
+#[derive(Clone, Debug, Default)]
+pub struct Span<'a> {
+    /// The text of the span.
+    pub string: std::string::String,
+    /// The metrics of each font in the span's chain, starting with its primary font.
+    ///
+    /// If this is empty, the span uses the chain of the builder.
+    pub fonts: Vec<&'a Metrics>,
+}
+
+/// A character waiting to be laid out.
+#[derive(Clone, Copy, Debug)]
+struct SpanChar {
+    /// The index of the character's span.
+    span: usize,
+    /// The index of the character within its span's string.
+    index: usize,
+    char: char,
+}
+
+impl SpanChar {
+    /// Tags every character of `string` with `span` and its index.
+    fn tag(span: usize, string: &str) -> impl Iterator<Item = Self> + '_ {
+        string
+            .chars()
+            .enumerate()
+            .map(move |(index, char)| Self { span, index, char })
     }
 }
 
 /// A string layout builder.
+///
+/// The builder lays out each character using the first font in its chain that has a glyph for
+/// that character, falling back to the primary font if none do.  Lines are always sized using the
+/// primary font of the builder's own chain.
 pub struct Builder<'a> {
-    font_metrics: &'a Metrics,
+    /// The metrics of each font in every chain, starting with the builder's own chain.
+    fonts: Vec<&'a Metrics>,
+    /// The range of `fonts` making up the chain of each span.
+    spans: Vec<std::ops::Range<usize>>,
     bounds: Rect,
     padded_h: Length,
+    paragraph_spacing: Length,
 
     //
     // User settings
     //
     alignment: anchor::X,
+    line_alignment: Option<LineAlignment>,
+    max_width: Option<Length>,
+    long_words: LongWords,
+    truncation: Option<&'a Truncation>,
+    tab_stops: Option<&'a TabStops>,
+    decorations: Decorations,
+
+    /// Spaces waiting to be laid out before the next word, if wrapping.
+    pending_spaces: Vec<SpanChar>,
+    /// The word currently being read, if wrapping.
+    pending_word: Vec<SpanChar>,
 
     /// The cursor, as an offset on the top-left of the string layout.
     cursor: point::Delta,
-    /// The metrics of the last character.
-    last_char_metrics: Option<&'a chars::Entry>,
+    /// The font and character metrics of the last character.
+    last_char_metrics: Option<(&'a Metrics, &'a chars::Entry)>,
 
     finished_lines: Vec<Line>,
     current_line: Line,
 }
 
 impl<'a> Builder<'a> {
-    /// Constructs a new layout builder with the given font metrics.
+    /// Constructs a new layout builder with the given primary font metrics.
     #[must_use]
     pub fn new(font_metrics: &'a Metrics) -> Self {
         Self {
             bounds: Rect::default(),
-            font_metrics,
+            fonts: vec![font_metrics],
+            spans: vec![],
             padded_h: font_metrics.padded_h(),
+            paragraph_spacing: 0,
             alignment: anchor::X::default(),
+            line_alignment: None,
+            max_width: None,
+            long_words: LongWords::default(),
+            truncation: None,
+            tab_stops: None,
+            decorations: Decorations::default(),
+            pending_spaces: vec![],
+            pending_word: vec![],
             cursor: point::Delta::default(),
             last_char_metrics: None,
             current_line: Line {
@@ -122,62 +430,396 @@ impl<'a> Builder<'a> {
                     w: 0,
                     h: font_metrics.char.h,
                 },
-                glyphs: GlyphSet::default(),
+                ..Line::default()
             },
             finished_lines: vec![],
         }
     }
 
+    /// Adds a fallback font to the end of the layout's font chain.
+    ///
+    /// Glyphs taken from this font will be tagged with its index in the chain, where the primary
+    /// font is at index 0.
+    #[must_use]
+    pub fn with_fallback(mut self, font_metrics: &'a Metrics) -> Self {
+        self.fonts.push(font_metrics);
+        self
+    }
+
     /// Changes the alignment of the layout.
+    ///
+    /// Unless overridden with [`Builder::with_line_alignment`], this also aligns each line
+    /// within the bounds of the layout.
     #[must_use]
     pub fn with_alignment(mut self, alignment: anchor::X) -> Self {
         self.alignment = alignment;
         self
     }
 
+    /// Aligns each line within the bounds of the layout independently of the layout's alignment.
+    ///
+    /// Justification only applies to lines ended by wrapping, and so has no effect unless there
+    /// is a maximum width.
+    #[must_use]
+    pub fn with_line_alignment(mut self, alignment: impl Into<LineAlignment>) -> Self {
+        self.line_alignment = Some(alignment.into());
+        self
+    }
+
+    /// Changes the distance between the tops of consecutive lines of the layout.
+    ///
+    /// Percentages are of the padded height of the primary font.
+    #[must_use]
+    pub fn with_line_height(mut self, line_height: LineHeight) -> Self {
+        self.padded_h = line_height.resolve(self.fonts[0]);
+        self
+    }
+
+    /// Adds `spacing` pixels between paragraphs; that is, wherever lines are separated by one or
+    /// more blank lines.
+    ///
+    /// By default, there is no such spacing, and blank lines take up no space.
+    #[must_use]
+    pub fn with_paragraph_spacing(mut self, spacing: Length) -> Self {
+        self.paragraph_spacing = spacing;
+        self
+    }
+
+    /// Wraps the layout at word boundaries so that lines are at most `max_width` wide.
+    ///
+    /// Widths take kerning and width overrides into account, in the same way as
+    /// [`Metrics::span_w_str`].  Spaces at a wrap point are dropped.
+    ///
+    /// If there is also a truncation (see [`Builder::with_truncation`]), lines are truncated to
+    /// the maximum width instead.
+    #[must_use]
+    pub fn with_max_width(mut self, max_width: Length) -> Self {
+        self.max_width = Some(max_width);
+        self
+    }
+
+    /// Changes how the layout handles words too long to fit within the maximum width.
+    ///
+    /// This has no effect unless there is a maximum width.
+    #[must_use]
+    pub fn with_long_words(mut self, long_words: LongWords) -> Self {
+        self.long_words = long_words;
+        self
+    }
+
+    /// Truncates, rather than wraps, lines that are wider than the maximum width.
+    ///
+    /// Each line is cut down to the most characters that will fit alongside the ellipsis, taking
+    /// kerning and width overrides into account.  If even the ellipsis won't fit, the line is
+    /// emptied.  This has no effect unless there is a maximum width.
+    #[must_use]
+    pub fn with_truncation(mut self, truncation: &'a Truncation) -> Self {
+        self.truncation = Some(truncation);
+        self
+    }
+
+    /// Lays out tabs by moving to the next of `tab_stops`, rather than as ordinary glyphs.
+    ///
+    /// The text following each tab, up to the next tab or line break, is aligned to the stop.
+    /// Tabs past the last stop are laid out as spaces.
+    #[must_use]
+    pub fn with_tab_stops(mut self, tab_stops: &'a TabStops) -> Self {
+        self.tab_stops = Some(tab_stops);
+        self
+    }
+
+    /// Decorates each laid-out line with `decorations`.
+    #[must_use]
+    pub fn with_decorations(mut self, decorations: Decorations) -> Self {
+        self.decorations = decorations;
+        self
+    }
+
     /// Builds the layout for a given string.
     #[must_use]
     pub fn build(mut self, string: std::string::String) -> String {
-        if string.is_empty() {
-            // No characters in the string.
-            return String::default();
-        };
+        self.spans.push(0..self.fonts.len());
+        self.layout_spans(vec![string]).pop().unwrap_or_default()
+    }
 
-        self.do_layout(&string);
+    /// Builds the layout for a sequence of spans, flowing them continuously.
+    ///
+    /// The result has one laid-out string per span, in the same order.  Each shares the bounds of
+    /// the whole layout, and so each can be rendered separately while keeping its position
+    /// relative to the others.  Kerning applies across span boundaries only where the adjacent
+    /// characters come from the same font.
+    #[must_use]
+    pub fn build_spans(mut self, spans: Vec<Span<'a>>) -> Vec<String> {
+        let own_chain = 0..self.fonts.len();
+        let mut strings = Vec::with_capacity(spans.len());
 
-        let mut glyphs = GlyphSet::default();
-        for line in self.finished_lines {
-            let mut line_glyphs = line.glyphs;
-            line_glyphs.realign(self.alignment, line.size.w, self.bounds.size.w);
-            glyphs.merge(line_glyphs);
+        for span in spans {
+            if span.fonts.is_empty() {
+                self.spans.push(own_chain.clone());
+            } else {
+                let start = self.fonts.len();
+                self.fonts.extend(span.fonts);
+                self.spans.push(start..self.fonts.len());
+            }
+            strings.push(span.string);
         }
 
-        String {
-            string,
-            bounds: self.bounds,
-            glyphs,
-        }
+        self.layout_spans(strings)
     }
 
     /// Pretends to lay out a given string, but only retrieves the bounds.
     #[must_use]
     pub fn dry_run(mut self, string: &str) -> Rect {
         // TODO: disable glyph storage?
-        self.do_layout(string);
+        self.spans.push(0..self.fonts.len());
+        self.do_layout(&SpanChar::tag(0, string).collect::<Vec<_>>());
         self.bounds
     }
 
-    fn do_layout(&mut self, string: &str) {
-        for char in string.chars() {
-            match char {
-                '\r' => self.carriage_return(),
-                '\n' => self.line_feed(),
-                c => self.layout_char(c),
+    fn layout_spans(mut self, strings: Vec<std::string::String>) -> Vec<String> {
+        if strings.iter().all(std::string::String::is_empty) {
+            // No characters in the string.
+            return strings
+                .into_iter()
+                .map(|string| String {
+                    string,
+                    ..String::default()
+                })
+                .collect();
+        }
+
+        let chars: Vec<_> = strings
+            .iter()
+            .enumerate()
+            .flat_map(|(span, string)| SpanChar::tag(span, string))
+            .collect();
+        self.do_layout(&chars);
+
+        let line_alignment = self
+            .line_alignment
+            .unwrap_or(LineAlignment::Anchor(self.alignment));
+
+        let mut glyphs = vec![GlyphSet::default(); strings.len()];
+        for line in self.finished_lines {
+            let extra = self.bounds.size.w - line.size.w;
+            let (anchor, justify) = match line_alignment {
+                LineAlignment::Anchor(anchor) => (anchor, false),
+                LineAlignment::Justify(anchor) => (anchor, line.is_justifiable()),
+            };
+
+            for (span, mut line_glyphs) in line.spans.into_iter().enumerate() {
+                if justify {
+                    line_glyphs.justify(&line.gaps, extra);
+                } else {
+                    line_glyphs.realign(anchor, line.size.w, self.bounds.size.w);
+                }
+                glyphs[span].merge(line_glyphs);
             }
         }
 
+        strings
+            .into_iter()
+            .zip(glyphs)
+            .map(|(string, glyphs)| String {
+                string,
+                bounds: self.bounds,
+                baseline: self.fonts[0].baseline,
+                decorations: self.decorations.layout(self.fonts[0], &glyphs),
+                glyphs,
+            })
+            .collect()
+    }
+
+    fn do_layout(&mut self, chars: &[SpanChar]) {
+        if let (Some(max_width), Some(truncation)) = (self.max_width, self.truncation) {
+            let truncated: Vec<SpanChar> = chars
+                .split(|c| c.char == '\n')
+                .map(|line| self.truncate(line, max_width, truncation))
+                .collect::<Vec<_>>()
+                .join(&SpanChar {
+                    span: 0,
+                    index: 0,
+                    char: '\n',
+                });
+            self.layout_chars(&truncated);
+        } else {
+            self.layout_chars(chars);
+        }
+
         // Implicit newline at the end to tidy things up:
+        self.flush_word();
         self.line_feed();
+
+        // Justified lines stretch to the wrap width, not just to the widest line.
+        if let (Some(LineAlignment::Justify(_)), Some(max_width)) =
+            (self.line_alignment, self.max_width)
+        {
+            if self.finished_lines.iter().any(Line::is_justifiable) {
+                self.bounds.size.w = self.bounds.size.w.max(max_width);
+            }
+        }
+    }
+
+    fn layout_chars(&mut self, chars: &[SpanChar]) {
+        for (i, &c) in chars.iter().enumerate() {
+            match c.char {
+                '\r' => {
+                    self.flush_word();
+                    self.carriage_return();
+                }
+                '\n' => {
+                    self.flush_word();
+                    if self.current_line.size.w == 0 {
+                        self.paragraph_break();
+                    } else {
+                        self.line_feed();
+                    }
+                }
+                '\t' if self.tab_stops.is_some() => {
+                    self.flush_word();
+                    self.tab(c, &chars[i + 1..]);
+                }
+                _ if self.max_width.is_none() => self.layout_char(c),
+                char if is_breaking_space(char) => {
+                    if !self.pending_word.is_empty() {
+                        self.flush_word();
+                    }
+                    self.pending_spaces.push(c);
+                }
+                _ => self.pending_word.push(c),
+            }
+        }
+    }
+
+    /// Truncates `line` to fit within `max_width`, if it doesn't already.
+    fn truncate(
+        &self,
+        line: &[SpanChar],
+        max_width: Length,
+        truncation: &Truncation,
+    ) -> Vec<SpanChar> {
+        if self.measure(0, None, line) <= max_width {
+            return line.to_vec();
+        }
+
+        (0..line.len())
+            .rev()
+            .map(|keep| truncation.at.cut(line, &truncation.ellipsis, keep))
+            .find(|cut| self.measure(0, None, cut) <= max_width)
+            .unwrap_or_default()
+    }
+
+    /// Lays out any pending spaces and word, wrapping first if they don't fit on the line.
+    fn flush_word(&mut self) {
+        let Some(max_width) = self.max_width else {
+            return;
+        };
+
+        let spaces = std::mem::take(&mut self.pending_spaces);
+        let word = std::mem::take(&mut self.pending_word);
+
+        if self.current_line.size.w == 0 || self.fits(spaces.iter().chain(&word), max_width) {
+            for c in spaces {
+                self.layout_char(c);
+            }
+        } else if word.is_empty() {
+            // Trailing spaces that don't fit can just disappear.
+            return;
+        } else {
+            self.wrap_line();
+        }
+
+        for c in word {
+            if self.long_words == LongWords::Break
+                && self.current_line.size.w != 0
+                && !self.fits(&[c], max_width)
+            {
+                self.wrap_line();
+            }
+            self.layout_char(c);
+        }
+    }
+
+    /// Moves to the next tab stop, aligning the text up to the next tab or line break in `rest`.
+    ///
+    /// When wrapping, a tab whose text wouldn't fit within the maximum width wraps onto the next
+    /// line before moving to a stop.
+    fn tab(&mut self, tab: SpanChar, rest: &[SpanChar]) {
+        let start = self.current_line.size.w;
+        let Some(stop) = self.tab_stops.and_then(|stops| stops.next(start)) else {
+            self.layout_char(SpanChar { char: ' ', ..tab });
+            return;
+        };
+
+        let field = rest
+            .iter()
+            .position(|c| matches!(c.char, '\t' | '\n' | '\r'))
+            .map_or(rest, |end| &rest[..end]);
+        let point = match stop.alignment {
+            tab::TabAlignment::Left => None,
+            tab::TabAlignment::Right => Some(field.len()),
+            tab::TabAlignment::Decimal(point) => Some(
+                field
+                    .iter()
+                    .position(|c| c.char == point)
+                    .unwrap_or(field.len()),
+            ),
+        };
+        // The aligned character must start at the stop, so the text before it ends there.
+        let before = point.map_or(0, |point| self.measure(0, None, &field[..point]));
+        let x = (stop.pos - before).max(start);
+
+        // If the field won't fit at the stop, it'll be wrapped away from it, so wrap first to keep
+        // it aligned on the next line.
+        if let Some(max_width) = self.max_width {
+            if start != 0 && max_width < x + self.measure(0, None, field) {
+                self.wrap_line();
+                return self.tab(tab, rest);
+            }
+        }
+
+        self.current_line.span_mut(tab.span).chars.push(CharBox {
+            index: tab.index,
+            rect: Rect {
+                top_left: Point {
+                    x: start,
+                    y: self.cursor.dy,
+                },
+                size: Size {
+                    w: x - start,
+                    h: self.fonts[0].char.h,
+                },
+            },
+        });
+
+        self.cursor.dx = x;
+        self.current_line.size.w = x;
+        self.last_char_metrics = None;
+    }
+
+    /// Checks whether `chars` would fit on the current line without exceeding `max_width`.
+    fn fits<'c>(&self, chars: impl IntoIterator<Item = &'c SpanChar>, max_width: Length) -> bool {
+        self.measure(self.current_line.size.w, self.last_char_metrics, chars) <= max_width
+    }
+
+    /// Calculates the width of a line of `width` pixels ending in a character with metrics
+    /// `last_char_metrics`, once `chars` have been added to it.
+    fn measure<'c>(
+        &self,
+        mut width: Length,
+        mut last_char_metrics: Option<(&'a Metrics, &'a chars::Entry)>,
+        chars: impl IntoIterator<Item = &'c SpanChar>,
+    ) -> Length {
+        for &SpanChar { span, char, .. } in chars {
+            let (_, font_metrics) = self.font_for(span, char);
+            let char_metrics = &font_metrics.chars[char];
+            if let Some(last) = last_char_metrics.replace((font_metrics, char_metrics)) {
+                width += kerning(last, font_metrics, char);
+            }
+            width += char_metrics.width;
+        }
+
+        width
     }
 
     fn carriage_return(&mut self) {
@@ -185,6 +827,12 @@ impl<'a> Builder<'a> {
         self.last_char_metrics = None;
     }
 
+    /// Ends the current line early, because the next word doesn't fit on it.
+    fn wrap_line(&mut self) {
+        self.current_line.wrapped = true;
+        self.line_feed();
+    }
+
     fn line_feed(&mut self) {
         if self.current_line.size.w == 0 {
             return;
@@ -203,47 +851,392 @@ impl<'a> Builder<'a> {
         self.current_line.size.h = self.padded_h;
     }
 
-    fn layout_char(&mut self, char: char) {
-        let char_metrics = &self.font_metrics.chars[char];
+    /// Moves the current, blank, line down by the paragraph spacing, if it isn't already.
+    fn paragraph_break(&mut self) {
+        if self.finished_lines.is_empty() || self.current_line.after_paragraph {
+            return;
+        }
+
+        self.current_line.after_paragraph = true;
+        self.cursor.dy += self.paragraph_spacing;
+        self.current_line.size.h += self.paragraph_spacing;
+    }
+
+    fn layout_char(&mut self, SpanChar { span, index, char }: SpanChar) {
+        let (font, font_metrics) = self.font_for(span, char);
+        let char_metrics = &font_metrics.chars[char];
         self.current_line.size.w += char_metrics.width;
 
-        if let Some(metrics) = self.last_char_metrics.replace(char_metrics) {
-            self.move_right_with_kerning(char, metrics);
+        if let Some(last) = self.last_char_metrics.replace((font_metrics, char_metrics)) {
+            self.move_right_with_kerning(last, font_metrics, char);
+        }
+
+        if !is_breaking_space(char) {
+            self.current_line.has_word = true;
+        } else if self.current_line.has_word {
+            self.current_line.gaps.push(self.cursor.dx);
         }
 
-        let src = self.char_src_rect(char, char_metrics);
-        self.current_line.glyphs.push(src, self.cursor);
+        let src = char_src_rect(font_metrics, char, char_metrics);
+        let char_box = Rect {
+            top_left: Point {
+                x: self.cursor.dx,
+                y: self.cursor.dy,
+            },
+            size: Size {
+                w: char_metrics.width,
+                h: self.fonts[0].char.h,
+            },
+        };
+
+        let glyphs = self.current_line.span_mut(span);
+        glyphs.push(font, src, self.cursor);
+        glyphs.chars.push(CharBox {
+            index,
+            rect: char_box,
+        });
     }
 
-    fn move_right_with_kerning(&mut self, char: char, metrics: &chars::Entry) {
-        let kerning = metrics.kerning(char);
-        self.cursor.dx += metrics.width + kerning;
+    /// Finds the first font in the chain of `span` with a glyph for `char`.
+    ///
+    /// This returns both the index of the font in the chain and its metrics.
+    fn font_for(&self, span: usize, char: char) -> (usize, &'a Metrics) {
+        let chain = &self.fonts[self.spans[span].clone()];
+        let font = chain
+            .iter()
+            .position(|f| f.has_glyph(char))
+            .unwrap_or_default();
+        (font, chain[font])
+    }
+
+    fn move_right_with_kerning(
+        &mut self,
+        last: (&Metrics, &chars::Entry),
+        font_metrics: &Metrics,
+        char: char,
+    ) {
+        let kerning = kerning(last, font_metrics, char);
+        self.cursor.dx += last.1.width + kerning;
         self.current_line.size.w += kerning;
     }
+}
 
-    fn char_src_rect(&self, char: char, metrics: &chars::Entry) -> Rect {
-        // TODO: cache
-        let src_top_left = self.font_metrics.glyph_top_left(char);
-        let size = Size {
-            w: metrics.width,
-            h: self.font_metrics.char.h,
-        };
-        src_top_left.to_rect(size, anchor::Anchor::TOP_LEFT)
+/// Calculates the kerning between the last character, with metrics `last`, and `char`.
+///
+/// Kerning tables only apply between characters of the same font; otherwise, we use the
+/// default spacing of the last character's font.
+fn kerning(last: (&Metrics, &chars::Entry), font_metrics: &Metrics, char: char) -> Length {
+    let (last_font, last_char) = last;
+    if std::ptr::eq(last_font, font_metrics) {
+        last_char.kerning(char)
+    } else {
+        last_char.default_kerning
     }
 }
 
+/// Is `c` a space at which we can wrap a line?
+fn is_breaking_space(c: char) -> bool {
+    c.is_whitespace() && !matches!(c, '\u{a0}' | '\u{2007}' | '\u{202f}')
+}
+
+fn char_src_rect(font_metrics: &Metrics, char: char, metrics: &chars::Entry) -> Rect {
+    // TODO: cache
+    let src_top_left = font_metrics.glyph_top_left(char);
+    let size = Size {
+        w: metrics.width,
+        h: font_metrics.char.h,
+    };
+    src_top_left.to_rect(size, anchor::Anchor::TOP_LEFT)
+}
+
 #[derive(Clone, Debug, Default)]
 struct Line {
     /// The size, including any padding from the previous line.
     size: Size,
-    glyphs: GlyphSet,
+    /// The glyphs on this line, grouped by span.
+    spans: Vec<GlyphSet>,
+    /// The offsets of the spaces making up the word gaps on this line.
+    gaps: Vec<Length>,
+    /// Whether this line has any characters other than spaces yet.
+    has_word: bool,
+    /// Whether this line was ended by wrapping, rather than by a line break or the end of text.
+    wrapped: bool,
+    /// Whether this line starts a paragraph after a blank line.
+    after_paragraph: bool,
+}
+
+impl Line {
+    /// Gets the glyphs on this line for `span`.
+    fn span_mut(&mut self, span: usize) -> &mut GlyphSet {
+        if self.spans.len() <= span {
+            self.spans.resize_with(span + 1, GlyphSet::default);
+        }
+        &mut self.spans[span]
+    }
+
+    /// Can this line be justified?  Only wrapped lines with word gaps can.
+    fn is_justifiable(&self) -> bool {
+        self.wrapped && !self.gaps.is_empty()
+    }
+}
+
+/// A single glyph to be rendered, as placed in layout order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Placement {
+    /// The index of the glyph's font in the font chain, where the primary font is at index 0.
+    pub font: usize,
+    /// The glyph's source rectangle.
+    pub src: Rect,
+    /// Where to render the glyph (as a delta against the top-left point).
+    pub dst: point::Delta,
 }
 
 /// A representation of a glyph to be rendered.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Glyph<'a> {
+    /// The index of the glyph's font in the font chain, where the primary font is at index 0.
+    pub font: usize,
     /// The glyph's source rectangle.
     pub src: Rect,
     /// Where to render the glyph (as a delta against the top-left points, assuming the size is the same as `src`).
     pub dsts: &'a [point::Delta],
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        super::metrics::{kerning, Spec},
+        *,
+    };
+
+    fn font(atlas: &str, code_point_rows: u8) -> Metrics {
+        Spec {
+            char: Size { w: 4, h: 6 },
+            pad: Size { w: 1, h: 1 },
+            atlas: [(8, atlas)].into_iter().collect(),
+            code_point_rows,
+            ..Spec::default()
+        }
+        .into_metrics()
+        .expect("should not fail to expand metrics")
+    }
+
+    /// Tests that characters missing from the primary font are taken from the first fallback
+    /// that has them.
+    #[test]
+    fn fallback_chain() {
+        let primary = font("", 8);
+        let symbols = font("★", 0);
+        let more_symbols = font("★☆", 0);
+
+        let str = Builder::new(&primary)
+            .with_fallback(&symbols)
+            .with_fallback(&more_symbols)
+            .build("a★☆コ".to_owned());
+
+        let mut glyphs: Vec<_> = str
+            .glyphs
+            .into_iter()
+            .map(|g| (g.dsts[0].dx, g.font, g.src.top_left))
+            .collect();
+        glyphs.sort_unstable_by_key(|(dx, _, _)| *dx);
+
+        assert_eq!(
+            vec![
+                (0, 0, primary.glyph_top_left('a')),
+                (5, 1, symbols.glyph_top_left('★')),
+                (10, 2, more_symbols.glyph_top_left('☆')),
+                // Nothing has this glyph, so it comes from the primary font.
+                (15, 0, Point::default()),
+            ],
+            glyphs
+        );
+    }
+
+    /// Tests that kerning pairs only apply between characters laid out in the same font.
+    #[test]
+    fn fallback_chain_kerning() {
+        // Each font tucks its own glyph, and the other font's, closer after its own glyph.
+        let kerned = |char: &str, atlas: &str, code_point_rows| {
+            let class = || [("c".to_owned(), char.to_owned())].into_iter().collect();
+            Spec {
+                char: Size { w: 4, h: 6 },
+                pad: Size { w: 1, h: 1 },
+                kerning: kerning::Spec {
+                    left: class(),
+                    right: [("r".to_owned(), "a★".to_owned())].into_iter().collect(),
+                    pairs: [(("c".to_owned(), "r".to_owned()), 0)]
+                        .into_iter()
+                        .collect(),
+                },
+                atlas: [(8, atlas)].into_iter().collect(),
+                code_point_rows,
+                ..Spec::default()
+            }
+            .into_metrics()
+            .expect("should not fail to expand metrics")
+        };
+        let primary = kerned("a", "", 8);
+        let symbols = kerned("★", "★", 0);
+
+        let str = Builder::new(&primary)
+            .with_fallback(&symbols)
+            .build("a★a★★aa".to_owned());
+
+        let mut dxs: Vec<_> = str
+            .glyphs
+            .into_iter()
+            .flat_map(|g| g.dsts.iter().map(|d| d.dx))
+            .collect();
+        dxs.sort_unstable();
+        assert_eq!(vec![0, 5, 10, 15, 19, 24, 28], dxs);
+    }
+
+    /// Tests that a maximum width wraps lines at word boundaries, dropping the spaces there.
+    #[test]
+    fn wrap_words() {
+        let font = font("", 8);
+        let bounds = Builder::new(&font).with_max_width(12).dry_run("ab cd  e");
+        // Each space-separated word is too wide to share a line with its neighbours.
+        assert_eq!(Size { w: 9, h: 20 }, bounds.size);
+    }
+
+    /// Tests both policies for words too long to fit within the maximum width.
+    #[test]
+    fn wrap_long_words() {
+        let font = font("", 8);
+        let overflow = Builder::new(&font).with_max_width(12).dry_run("abcdef");
+        assert_eq!(Size { w: 29, h: 6 }, overflow.size);
+
+        let broken = Builder::new(&font)
+            .with_max_width(12)
+            .with_long_words(LongWords::Break)
+            .dry_run("abcdef");
+        assert_eq!(Size { w: 9, h: 20 }, broken.size);
+    }
+
+    /// Tests truncation at each position.
+    #[test]
+    fn truncate() {
+        let font = font("", 8);
+        let layout = |at| {
+            let truncation = Truncation {
+                at,
+                ellipsis: "~".to_owned(),
+            };
+            Builder::new(&font)
+                .with_max_width(20)
+                .with_truncation(&truncation)
+                .build("abc defg\nhi".to_owned())
+        };
+
+        // Each line fits four characters, including the ellipsis and any spaces next to it.
+        for (at, expected) in [
+            (TruncateAt::Start, "~efg"),
+            (TruncateAt::Middle, "ab~g"),
+            (TruncateAt::End, "abc~"),
+        ] {
+            let str = layout(at);
+            assert_eq!(Size { w: 19, h: 13 }, str.bounds.size, "{at:?}");
+            assert_eq!("abc defg\nhi", str.string, "original string should be kept");
+
+            let mut glyphs: Vec<_> = str
+                .glyphs
+                .into_iter()
+                .flat_map(|g| g.dsts.iter().map(move |d| (d.dy, d.dx, g.src.top_left)))
+                .collect();
+            glyphs.sort_unstable_by_key(|(dy, dx, _)| (*dy, *dx));
+            let first_line: Vec<_> = glyphs
+                .iter()
+                .filter(|(dy, _, _)| *dy == 0)
+                .map(|(_, _, src)| font.glyph_char(*src).unwrap())
+                .collect();
+            assert_eq!(expected, first_line.iter().collect::<std::string::String>());
+        }
+    }
+
+    /// Tests that glyphs come out in layout order, and grouped in order of first appearance.
+    #[test]
+    fn glyph_order() {
+        let font = font("", 8);
+        let str = Builder::new(&font).build("abab\nba".to_owned());
+
+        let in_order: std::string::String = str
+            .glyphs
+            .in_order()
+            .map(|g| font.glyph_char(g.src.top_left).unwrap())
+            .collect();
+        assert_eq!("ababba", in_order);
+
+        let grouped: Vec<_> = str
+            .glyphs
+            .into_iter()
+            .map(|g| (font.glyph_char(g.src.top_left).unwrap(), g.dsts.len()))
+            .collect();
+        assert_eq!(vec![('a', 3), ('b', 3)], grouped);
+    }
+
+    /// Tests that justification spreads space across the word gaps of wrapped lines only, and
+    /// aligns other lines to its anchor.
+    #[test]
+    fn justify() {
+        let font = crate::backends::terminal::metrics();
+        let str = Builder::new(&font)
+            .with_max_width(7)
+            .with_line_alignment(LineAlignment::Justify(anchor::X::Right))
+            .build("a b c dddddd\ne f".to_owned());
+
+        let dsts: Vec<_> = str
+            .glyphs
+            .in_order()
+            .map(|g| (g.dst.dx, g.dst.dy))
+            .collect();
+        assert_eq!(
+            vec![
+                (0, 0),
+                (1, 0),
+                (3, 0),
+                (4, 0),
+                (6, 0),
+                (1, 1),
+                (2, 1),
+                (3, 1),
+                (4, 1),
+                (5, 1),
+                (6, 1),
+                (4, 2),
+                (5, 2),
+                (6, 2)
+            ],
+            dsts
+        );
+        // Justified lines fill the wrap width, spreading the extra space over both gaps.
+        assert_eq!(7, str.bounds.size.w);
+        assert_eq!(vec![Rect::new(1, 0, 2, 1)], str.selection(1..2));
+        assert_eq!(vec![Rect::new(4, 0, 2, 1)], str.selection(3..4));
+    }
+
+    /// Tests line height and paragraph spacing overrides, in both layouts and dry runs.
+    #[test]
+    fn line_spacing() {
+        let font = crate::backends::terminal::metrics();
+        let builder = || {
+            Builder::new(&font)
+                .with_line_height(LineHeight::Pixels(3))
+                .with_paragraph_spacing(2)
+        };
+        let text = "a\nb\n\n\nc";
+
+        let str = builder().build(text.to_owned());
+        let dys: Vec<_> = str.glyphs.in_order().map(|g| g.dst.dy).collect();
+        assert_eq!(vec![0, 3, 8], dys);
+        assert_eq!(Size { w: 1, h: 9 }, str.bounds.size);
+        assert_eq!(str.bounds, builder().dry_run(text));
+
+        // Without paragraph spacing, blank lines take up no space.
+        let str = Builder::new(&font)
+            .with_line_height(LineHeight::Percent(200))
+            .build(text.to_owned());
+        assert_eq!(Size { w: 1, h: 5 }, str.bounds.size);
+    }
+}
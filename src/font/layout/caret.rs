@@ -0,0 +1,176 @@
+//! Hit-testing, carets, and selections on laid-out strings.
+//!
+//! Character indices count characters, not bytes, from the start of the laid-out string.  Some
+//! characters have no position of their own: line breaks, spaces dropped when wrapping, and
+//! characters cut by truncation.  Carets for such characters sit just after the last laid-out
+//! character before them.
+
+use std::{collections::BTreeMap, ops::Range};
+
+use super::{
+    super::super::metrics::{Length, Point, Rect, Size},
+    CharBox, GlyphSet, String,
+};
+
+impl String {
+    /// Finds the index of the laid-out character nearest to `point`.
+    ///
+    /// This picks the line nearest to `point` vertically, then the character on that line nearest
+    /// to `point` horizontally.  If nothing was laid out, there is no such character.
+    #[must_use]
+    pub fn char_at(&self, point: Point) -> Option<usize> {
+        let dx = point.x - self.bounds.top_left.x;
+        let dy = point.y - self.bounds.top_left.y;
+
+        let line = self
+            .char_boxes()
+            .min_by_key(|c| distance(dy, c.rect.top_left.y, c.rect.size.h))?
+            .rect
+            .top_left
+            .y;
+
+        self.char_boxes()
+            .filter(|c| c.rect.top_left.y == line)
+            .min_by_key(|c| distance(dx, c.rect.top_left.x, c.rect.size.w))
+            .map(|c| c.index)
+    }
+
+    /// Gets the rectangle of a one-pixel-wide caret placed just before the character at `index`.
+    ///
+    /// An `index` past the end of the string places the caret after the last character.  If
+    /// nothing was laid out, there is no caret, as the layout doesn't know how tall it would be.
+    #[must_use]
+    pub fn caret(&self, index: usize) -> Option<Rect> {
+        let (x, char_box) = if let Some(c) = self.char_boxes().find(|c| c.index == index) {
+            (c.rect.top_left.x, c)
+        } else if let Some(c) = self
+            .char_boxes()
+            .filter(|c| c.index < index)
+            .max_by_key(|c| c.index)
+        {
+            (c.rect.top_left.x + c.rect.size.w, c)
+        } else {
+            let c = self.char_boxes().next()?;
+            (c.rect.top_left.x, c)
+        };
+
+        Some(Rect {
+            top_left: self.bounds.top_left.offset(x, char_box.rect.top_left.y),
+            size: Size {
+                w: 1,
+                h: char_box.rect.size.h,
+            },
+        })
+    }
+
+    /// Gets the rectangles covering the laid-out characters with indices in `range`.
+    ///
+    /// There is one rectangle for each line containing part of the range, in order from top to
+    /// bottom.
+    #[must_use]
+    pub fn selection(&self, range: Range<usize>) -> Vec<Rect> {
+        self.glyphs
+            .lines(range)
+            .into_iter()
+            .map(|Rect { top_left, size }| Rect {
+                top_left: self.bounds.top_left.offset(top_left.x, top_left.y),
+                size,
+            })
+            .collect()
+    }
+
+    fn char_boxes(&self) -> std::slice::Iter<'_, CharBox> {
+        self.glyphs.chars.iter()
+    }
+}
+
+impl GlyphSet {
+    /// Gets the boxes, relative to the string's top-left, covering the laid-out characters with
+    /// indices in `range`; there is one box per line, in order from top to bottom.
+    pub(super) fn lines(&self, range: Range<usize>) -> Vec<Rect> {
+        // Maps each line's top to its leftmost and rightmost extent, and its height.
+        let mut lines: BTreeMap<Length, (Length, Length, Length)> = BTreeMap::new();
+        for c in self.chars.iter().filter(|c| range.contains(&c.index)) {
+            let Rect { top_left, size } = c.rect;
+            let (left, right) = (top_left.x, top_left.x + size.w);
+            lines
+                .entry(top_left.y)
+                .and_modify(|(l, r, _)| {
+                    *l = left.min(*l);
+                    *r = right.max(*r);
+                })
+                .or_insert((left, right, size.h));
+        }
+
+        lines
+            .into_iter()
+            .map(|(y, (left, right, h))| Rect {
+                top_left: Point { x: left, y },
+                size: Size { w: right - left, h },
+            })
+            .collect()
+    }
+}
+
+/// Calculates how far `coord` is from the span of `size` pixels starting at `start`.
+fn distance(coord: Length, start: Length, size: Length) -> Length {
+    if coord < start {
+        start - coord
+    } else {
+        (coord - (start + size - 1)).max(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::Builder, *};
+    use crate::backends::terminal;
+
+    fn layout() -> String {
+        let metrics = terminal::metrics();
+        let mut str = Builder::new(&metrics)
+            .with_max_width(5)
+            .build("ab cd\nefgh ij".to_owned());
+        str.bounds.top_left = Point { x: 10, y: 20 };
+        str
+    }
+
+    /// Tests mapping points onto characters, including points outside the layout.
+    #[test]
+    fn char_at() {
+        let str = layout();
+        assert_eq!(Some(0), str.char_at(Point { x: 10, y: 20 }));
+        assert_eq!(Some(4), str.char_at(Point { x: 14, y: 20 }));
+        assert_eq!(Some(4), str.char_at(Point { x: 99, y: 0 }));
+        assert_eq!(Some(7), str.char_at(Point { x: 11, y: 21 }));
+        assert_eq!(Some(12), str.char_at(Point { x: 99, y: 99 }));
+        assert_eq!(None, String::default().char_at(Point::default()));
+    }
+
+    /// Tests caret placement, including at characters that weren't laid out.
+    #[test]
+    fn caret() {
+        let str = layout();
+        assert_eq!(Some(Rect::new(11, 20, 1, 1)), str.caret(1));
+        // The newline, and the space dropped at the wrap, aren't laid out.
+        assert_eq!(Some(Rect::new(15, 20, 1, 1)), str.caret(5));
+        assert_eq!(Some(Rect::new(14, 21, 1, 1)), str.caret(10));
+        assert_eq!(Some(Rect::new(12, 22, 1, 1)), str.caret(99));
+        assert_eq!(None, String::default().caret(0));
+    }
+
+    /// Tests that selections produce one rectangle per line.
+    #[test]
+    fn selection() {
+        let str = layout();
+        assert_eq!(
+            vec![
+                Rect::new(13, 20, 2, 1),
+                Rect::new(10, 21, 4, 1),
+                Rect::new(10, 22, 1, 1)
+            ],
+            str.selection(3..12)
+        );
+        assert!(str.selection(5..6).is_empty());
+    }
+}
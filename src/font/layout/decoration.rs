@@ -0,0 +1,132 @@
+//! Underline and strikethrough decorations on laid-out strings.
+//!
+//! Decorations are rectangles laid out alongside the glyphs of a string, and renderers fill them
+//! in the same colour as the glyphs.  Each decoration spans the laid-out characters of one line,
+//! so it follows any kerning, alignment, and truncation of that line.
+
+use super::{
+    super::{
+        super::metrics::{Rect, Size},
+        Metrics,
+    },
+    GlyphSet,
+};
+
+/// A set of line decorations.
+///
+/// The default set has no decorations.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Decorations {
+    /// Whether to underline each line.
+    pub underline: bool,
+    /// Whether to strike through each line.
+    pub strikethrough: bool,
+}
+
+impl Decorations {
+    /// Gets whether this set has no decorations.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        !(self.underline || self.strikethrough)
+    }
+
+    /// Lays out these decorations over `glyphs`, placing and sizing rows using `metrics`.
+    ///
+    /// The rectangles are relative to the top-left of the string, like the glyph destinations.
+    pub(super) fn layout(self, metrics: &Metrics, glyphs: &GlyphSet) -> Vec<Rect> {
+        if self.is_empty() {
+            return vec![];
+        }
+
+        let rows = [
+            (self.underline, metrics.underline),
+            (self.strikethrough, metrics.strikethrough),
+        ];
+        let h = metrics.decoration_thickness;
+
+        glyphs
+            .lines(0..usize::MAX)
+            .into_iter()
+            .filter(|line| line.size.w > 0)
+            .flat_map(|line| {
+                rows.into_iter()
+                    .filter(|(on, _)| *on)
+                    .map(move |(_, row)| Rect {
+                        top_left: line.top_left.offset(0, row),
+                        size: Size { w: line.size.w, h },
+                    })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        super::{super::metrics::Spec, Builder},
+        *,
+    };
+
+    /// Tests that decorations span each line's laid-out characters, on the rows given by the
+    /// font metrics.
+    #[test]
+    fn layout_lines() {
+        let metrics = Spec {
+            char: Size { w: 4, h: 6 },
+            pad: Size { w: 1, h: 1 },
+            strikethrough: Some(2),
+            ..Spec::default()
+        }
+        .into_metrics()
+        .expect("should not fail to expand metrics");
+
+        let str = Builder::new(&metrics)
+            .with_alignment(crate::metrics::anchor::X::Right)
+            .with_decorations(Decorations {
+                underline: true,
+                strikethrough: true,
+            })
+            .build("ab\nc".to_owned());
+
+        assert_eq!(
+            vec![
+                Rect::new(0, 5, 9, 1),
+                Rect::new(0, 2, 9, 1),
+                Rect::new(5, 12, 4, 1),
+                Rect::new(5, 9, 4, 1),
+            ],
+            str.decorations
+        );
+    }
+
+    /// Tests that decorations are as thick as the font metrics say, including when scaled.
+    #[test]
+    fn layout_thickness() {
+        let metrics = Spec {
+            char: Size { w: 4, h: 6 },
+            underline: Some(4),
+            decoration_thickness: Some(2),
+            ..Spec::default()
+        }
+        .scaled(2)
+        .into_metrics()
+        .expect("should not fail to expand metrics");
+
+        let str = Builder::new(&metrics)
+            .with_decorations(Decorations {
+                underline: true,
+                strikethrough: false,
+            })
+            .build("a".to_owned());
+
+        assert_eq!(vec![Rect::new(0, 8, 8, 4)], str.decorations);
+    }
+
+    /// Tests that undecorated layouts have no decorations.
+    #[test]
+    fn layout_none() {
+        let metrics = crate::backends::terminal::metrics();
+        let str = Builder::new(&metrics).build("abc".to_owned());
+        assert!(str.decorations.is_empty());
+    }
+}
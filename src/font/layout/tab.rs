@@ -0,0 +1,195 @@
+//! Tab stops.
+//!
+//! When a layout has tab stops, each tab moves the cursor to the next stop to the right of it,
+//! and the text between that tab and the next tab or line break is aligned to the stop.
+
+use super::super::super::metrics::Length;
+
+/// How text is aligned to a tab stop.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub enum TabAlignment {
+    /// The text starts at the stop.
+    #[default]
+    Left,
+    /// The text ends at the stop.
+    Right,
+    /// The first occurrence of the given character, such as a decimal point, starts at the stop.
+    ///
+    /// Text without that character ends at the stop, as if the stop were right-aligned.
+    Decimal(char),
+}
+
+/// A tab stop.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub struct TabStop {
+    /// The position of the stop, in pixels from the left of the layout.
+    pub pos: Length,
+    /// How text is aligned to the stop.
+    pub alignment: TabAlignment,
+}
+
+impl TabStop {
+    /// Constructs a left-aligned stop at `pos`.
+    #[must_use]
+    pub fn left(pos: Length) -> Self {
+        Self {
+            pos,
+            alignment: TabAlignment::Left,
+        }
+    }
+
+    /// Constructs a right-aligned stop at `pos`.
+    #[must_use]
+    pub fn right(pos: Length) -> Self {
+        Self {
+            pos,
+            alignment: TabAlignment::Right,
+        }
+    }
+
+    /// Constructs a stop at `pos` aligning the first occurrence of `point` to it.
+    #[must_use]
+    pub fn decimal(pos: Length, point: char) -> Self {
+        Self {
+            pos,
+            alignment: TabAlignment::Decimal(point),
+        }
+    }
+}
+
+/// A set of tab stops.
+///
+/// The default set has no stops, and so tabs are laid out as spaces.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct TabStops {
+    /// Explicit stops, in increasing order of position.
+    pub stops: Vec<TabStop>,
+    /// The interval between the left-aligned stops that follow the explicit stops, if any.
+    pub interval: Option<Length>,
+}
+
+impl TabStops {
+    /// Constructs a set of left-aligned stops every `interval` pixels.
+    #[must_use]
+    pub fn every(interval: Length) -> Self {
+        Self {
+            stops: vec![],
+            interval: Some(interval),
+        }
+    }
+
+    /// Constructs a set of explicit stops, with no stops past the last one.
+    #[must_use]
+    pub fn new(stops: impl IntoIterator<Item = TabStop>) -> Self {
+        Self {
+            stops: stops.into_iter().collect(),
+            interval: None,
+        }
+    }
+
+    /// Adds left-aligned stops every `interval` pixels past the explicit stops.
+    #[must_use]
+    pub fn then_every(mut self, interval: Length) -> Self {
+        self.interval = Some(interval);
+        self
+    }
+
+    /// Finds the first stop strictly to the right of `x`, if there is one.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ugly::font::layout::tab::{TabStop, TabStops};
+    ///
+    /// let stops = TabStops::new([TabStop::right(30)]).then_every(16);
+    /// assert_eq!(Some(TabStop::right(30)), stops.next(12));
+    /// assert_eq!(Some(TabStop::left(32)), stops.next(30));
+    /// assert_eq!(None, TabStops::default().next(0));
+    /// ```
+    #[must_use]
+    pub fn next(&self, x: Length) -> Option<TabStop> {
+        self.stops.iter().find(|s| x < s.pos).copied().or_else(|| {
+            self.interval
+                .filter(|&interval| 0 < interval)
+                .map(|interval| TabStop::left((x / interval + 1) * interval))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        super::{Builder, String},
+        *,
+    };
+    use crate::{backends::terminal, metrics::Rect};
+
+    fn layout(stops: &TabStops, str: &str) -> String {
+        let metrics = terminal::metrics();
+        Builder::new(&metrics)
+            .with_tab_stops(stops)
+            .build(str.to_owned())
+    }
+
+    /// Gets the horizontal position of each glyph, in layout order.
+    fn dxs(str: &String) -> Vec<Length> {
+        str.glyphs.in_order().map(|g| g.dst.dx).collect()
+    }
+
+    /// Tests tabbing to stops at a fixed interval.
+    #[test]
+    fn interval() {
+        let str = layout(&TabStops::every(4), "ab\tc\td\n\te");
+        assert_eq!(vec![0, 1, 4, 8, 4], dxs(&str));
+        // Tabs can be selected, and cover the space they skip.
+        assert_eq!(vec![Rect::new(2, 0, 2, 1)], str.selection(2..3));
+    }
+
+    /// Tests right- and decimal-aligned stops.
+    #[test]
+    fn aligned() {
+        let stops = TabStops::new([TabStop::right(6), TabStop::decimal(10, '.')]);
+        assert_eq!(
+            vec![0, 4, 5, 8, 9, 10, 11],
+            dxs(&layout(&stops, "a\tbc\t12.3"))
+        );
+        // Without a decimal point, decimal stops act like right-aligned stops.
+        assert_eq!(vec![0, 7, 8, 9], dxs(&layout(&stops, "a\t\t123")));
+    }
+
+    /// Tests that tabs wrap before their stops if the text after them wouldn't fit.
+    #[test]
+    fn wrapped() {
+        let metrics = terminal::metrics();
+        let str = Builder::new(&metrics)
+            .with_max_width(8)
+            .with_tab_stops(&TabStops::every(4))
+            .build("abcde\tfg\tzz".to_owned());
+        let dsts: Vec<_> = str
+            .glyphs
+            .in_order()
+            .map(|g| (g.dst.dx, g.dst.dy))
+            .collect();
+        assert_eq!(
+            vec![
+                (0, 0),
+                (1, 0),
+                (2, 0),
+                (3, 0),
+                (4, 0),
+                (4, 1),
+                (5, 1),
+                (4, 2),
+                (5, 2)
+            ],
+            dsts
+        );
+    }
+
+    /// Tests that tabs past the last stop are laid out as spaces.
+    #[test]
+    fn past_last_stop() {
+        let stops = TabStops::new([TabStop::left(2)]);
+        assert_eq!(vec![0, 2, 3, 4], dxs(&layout(&stops, "a\tb\tc")));
+    }
+}
@@ -4,10 +4,10 @@
 use std::{
     collections::{hash_map::Entry, HashMap},
     hash::Hash,
-    path::Path,
 };
 
 use super::Result;
+use crate::metrics;
 
 /// An index for a loaded font in a [Manager].
 ///
@@ -24,12 +24,18 @@ impl Default for Index {
 }
 
 /// A backend-agnostic, cached font manager.
+///
+/// Fonts that differ only in scale share the same data, which should hold their unscaled texture;
+/// backends stretch glyphs to the font's scale as they draw them.
 pub struct Manager<Font, Data>
 where
     Font: super::Map,
 {
-    /// The cache of already-loaded fonts.
-    cache: HashMap<Font::Id, Data>,
+    /// The cache of indices into `loaded` for already-loaded fonts.
+    cache: HashMap<Font::Id, usize>,
+
+    /// The data for each distinct font texture loaded so far, with the font it was loaded from.
+    loaded: Vec<(super::Font, Data)>,
 
     /// The font path set.
     font_set: Font,
@@ -47,6 +53,7 @@ where
     pub fn new(font_set: Font, metrics_set: Font::MetricsMap) -> Self {
         Self {
             cache: HashMap::new(),
+            loaded: Vec::new(),
             font_set,
             metrics_set,
         }
@@ -57,9 +64,24 @@ where
         &self.metrics_set
     }
 
+    /// Gets the font with the given font ID.
+    #[must_use]
+    pub fn font(&self, id: Font::Id) -> &super::Font {
+        self.font_set.get(id)
+    }
+
+    /// Maps the glyph source rectangle `src`, from the metrics of the given font ID, onto the
+    /// unscaled texture held in the font's data.
+    pub fn texture_rect(&self, id: Font::Id, src: metrics::Rect) -> metrics::Rect {
+        self.font_set.get(id).unscale(src)
+    }
+
     /// Gets the data for the given font ID.
     ///
-    /// If the font is not present, its texture will be loaded using `loader`.
+    /// If neither the font, nor any font differing from it only in scale, is present, its
+    /// texture will be loaded by passing the font to `loader`.  The loader should load the
+    /// [unscaled texture](super::Font::unscaled_texture).  The data is returned mutably, as some
+    /// backends need to change texture state (such as colour modulation) before rendering with it.
     ///
     /// # Errors
     ///
@@ -67,16 +89,59 @@ where
     pub fn data(
         &mut self,
         id: Font::Id,
-        mut loader: impl FnMut(&Path) -> Result<Data>,
-    ) -> Result<&Data> {
-        match self.cache.entry(id) {
-            Entry::Occupied(slot) => Ok(slot.into_mut()),
+        mut loader: impl FnMut(&super::Font) -> Result<Data>,
+    ) -> Result<&mut Data> {
+        let index = match self.cache.entry(id) {
+            Entry::Occupied(slot) => *slot.get(),
             Entry::Vacant(slot) => {
-                let path = &self.font_set.get(id).texture_path();
-                let data = loader(path)?;
-
-                Ok(slot.insert(data))
+                let font = self.font_set.get(id);
+                let shared = self.loaded.iter().position(|(f, _)| f.shares_texture(font));
+                let index = if let Some(index) = shared {
+                    index
+                } else {
+                    let data = loader(font)?;
+                    self.loaded.push((font.clone(), data));
+                    self.loaded.len() - 1
+                };
+                *slot.insert(index)
             }
+        };
+        Ok(&mut self.loaded[index].1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::resource::DefaultingHashMap;
+
+    /// Tests that fonts differing only in scale share their loaded data.
+    #[test]
+    fn shared_scaled_data() {
+        let font = super::super::Font::from_dir("test");
+        let fonts = DefaultingHashMap::new(
+            HashMap::from([(1, font.clone().scaled(2)), (2, font.clone().scaled(3))]),
+            font,
+        );
+        let metrics = DefaultingHashMap::new(HashMap::new(), super::super::Metrics::default());
+        let mut manager = Manager::new(fonts, metrics);
+
+        let mut loads = 0;
+        for id in [0, 1, 2, 1] {
+            let data = manager
+                .data(id, |f| {
+                    loads += 1;
+                    Ok(f.scale())
+                })
+                .expect("should load data");
+            assert_eq!(1, *data);
         }
+        assert_eq!(1, loads);
+        assert_eq!(
+            metrics::Rect::new(1, 2, 3, 4),
+            manager.texture_rect(2, metrics::Rect::new(3, 6, 9, 12))
+        );
     }
 }
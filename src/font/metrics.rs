@@ -1,5 +1,6 @@
 //! Font metrics.
 
+pub mod atlas;
 pub mod chars;
 pub mod kerning;
 pub mod width;
@@ -13,7 +14,7 @@ use crate::metrics::{Length, Point, Size};
 // constants:
 
 /// The number of columns in a font.
-const NUM_COLS: u8 = 32;
+pub(super) const NUM_COLS: u8 = 32;
 
 /// An on-disk font metrics specification.
 ///
@@ -37,22 +38,122 @@ pub struct Spec {
     /// Class-based kerning for specific characters.
     #[serde(default)]
     pub kerning: kerning::Spec,
+    /// Placements of characters onto the texture grid beyond the default code point mapping.
+    ///
+    /// This is how fonts supply glyphs for characters outside the first 256 code points.
+    #[serde(default)]
+    pub atlas: atlas::Spec,
+    /// The number of rows of the texture grid covered by the default code point mapping.
+    ///
+    /// This defaults to 8 rows (code points 0 to 255).  Fonts that only supply glyphs through the
+    /// atlas, such as symbol fonts, can set this to 0 so that layout falls back to other fonts
+    /// for every character not in the atlas.
+    #[serde(default = "default_code_point_rows")]
+    pub code_point_rows: u8,
+    /// The number of rows, from the top of a character, down to the baseline.
+    ///
+    /// Glyphs sit on the baseline, with any descenders hanging below it.  If absent, the baseline
+    /// is at the bottom of the character.
+    #[serde(default)]
+    pub baseline: Option<Length>,
+    /// The number of rows that the tallest glyphs rise above the baseline.
+    ///
+    /// If absent, glyphs are assumed to rise all the way to the top of the character.
+    #[serde(default)]
+    pub ascent: Option<Length>,
+    /// The number of rows that the deepest descenders fall below the baseline.
+    ///
+    /// If absent, descenders are assumed to fall all the way to the bottom of the character.
+    #[serde(default)]
+    pub descent: Option<Length>,
+    /// The row, from the top of a character, on which underlines are drawn.
+    ///
+    /// If absent, underlines are drawn on the row just below the baseline or, if the baseline is
+    /// at the bottom of the character, on the bottom row.
+    #[serde(default)]
+    pub underline: Option<Length>,
+    /// The row, from the top of a character, on which strikethroughs are drawn.
+    ///
+    /// If absent, strikethroughs are drawn halfway down the character.
+    #[serde(default)]
+    pub strikethrough: Option<Length>,
+    /// The number of rows that underlines and strikethroughs cover, starting at their rows.
+    ///
+    /// If absent, decorations are one row thick.
+    #[serde(default)]
+    pub decoration_thickness: Option<Length>,
+}
+
+fn default_code_point_rows() -> u8 {
+    8
 }
 
 impl Spec {
+    /// Scales every length in this metrics spec by `factor`.
+    ///
+    /// This is how fonts are rendered at integer scales: the spec describes the font as if its
+    /// texture had been upscaled by `factor` with nearest-neighbour sampling.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ugly::{font::metrics::Spec, metrics::Size};
+    ///
+    /// let spec = Spec {
+    ///     char: Size { w: 4, h: 6 },
+    ///     baseline: Some(5),
+    ///     ..Spec::default()
+    /// }
+    /// .scaled(3);
+    /// assert_eq!(Size { w: 12, h: 18 }, spec.char);
+    /// assert_eq!(Some(15), spec.baseline);
+    /// assert_eq!(3, spec.into_metrics().unwrap().decoration_thickness);
+    /// ```
+    #[must_use]
+    pub fn scaled(self, factor: u8) -> Self {
+        let factor = Length::from(factor);
+        let scale = |l: Length| l * factor;
+        let scale_size = |s: Size| Size {
+            w: scale(s.w),
+            h: scale(s.h),
+        };
+        Self {
+            char: scale_size(self.char),
+            pad: scale_size(self.pad),
+            width_overrides: self.width_overrides.scaled(factor),
+            kerning: self.kerning.scaled(factor),
+            baseline: self.baseline.map(scale),
+            ascent: self.ascent.map(scale),
+            descent: self.descent.map(scale),
+            underline: self.underline.map(scale),
+            strikethrough: self.strikethrough.map(scale),
+            decoration_thickness: Some(scale(self.decoration_thickness.unwrap_or(1))),
+            ..self
+        }
+    }
+
     /// Expands this metrics spec into a full metrics set.
     ///
-    /// This precomputes width overrides.
+    /// This precomputes width overrides and the glyph atlas.
     ///
     /// # Errors
     ///
     /// Fails if the metrics spec is ill-formed (eg, a width override tries to make a character
-    /// longer than its grid width).
+    /// longer than its grid width, or an atlas row is too long for the grid).
     pub fn into_metrics(self) -> super::Result<Metrics> {
+        let baseline = self.baseline.unwrap_or(self.char.h);
         Ok(Metrics {
             char: self.char,
             pad: self.pad,
             chars: chars::Table::new(self.width_overrides, self.char.w, self.kerning, self.pad.w)?,
+            atlas: self.atlas.into_table()?,
+            code_point_rows: self.code_point_rows,
+            baseline,
+            ascent: self.ascent.unwrap_or(baseline),
+            descent: self.descent.unwrap_or(self.char.h - baseline),
+            underline: self.underline.unwrap_or(baseline.min(self.char.h - 1)),
+            strikethrough: self.strikethrough.unwrap_or(self.char.h / 2),
+            decoration_thickness: self.decoration_thickness.unwrap_or(1),
         })
     }
 }
@@ -69,6 +170,22 @@ pub struct Metrics {
     pub pad: Size,
     /// Map of characters to their width and kerning information.
     pub chars: chars::Table,
+    /// Map of characters to their cells on the texture grid, where not the default.
+    pub atlas: atlas::Table,
+    /// The number of rows of the texture grid covered by the default code point mapping.
+    pub code_point_rows: u8,
+    /// The number of rows, from the top of a character, down to the baseline.
+    pub baseline: Length,
+    /// The number of rows that the tallest glyphs rise above the baseline.
+    pub ascent: Length,
+    /// The number of rows that the deepest descenders fall below the baseline.
+    pub descent: Length,
+    /// The row, from the top of a character, on which underlines are drawn.
+    pub underline: Length,
+    /// The row, from the top of a character, on which strikethroughs are drawn.
+    pub strikethrough: Length,
+    /// The number of rows that underlines and strikethroughs cover, starting at their rows.
+    pub decoration_thickness: Length,
 }
 
 impl Metrics {
@@ -139,14 +256,65 @@ impl Metrics {
     /// and this metrics structure (for height).  Since things that want the glyph top-left
     /// tend to have their own copy of the character map information by the time they call
     /// `glyph_top_left`, we don't expose any duplicate way of getting the size.
+    ///
+    /// Characters in the atlas are placed on their atlas cell; other characters up to code point
+    /// 255 are placed on the grid cell matching their code point.  Any other character is mapped
+    /// onto the top-left cell.
     #[must_use]
     pub fn glyph_top_left(&self, char: char) -> Point {
-        // TODO(@MattWindsor91): glyph atlasing for >ASCII characters
+        if let Some(cell) = self.atlas.cell(char) {
+            return Point {
+                x: glyph_axis(cell.col, self.padded_w()),
+                y: Length::from(cell.row) * self.padded_h(),
+            };
+        }
+
         char_to_ascii(char).map_or_else(Point::default, |g| Point {
             x: glyph_axis(glyph_col(g), self.padded_w()),
             y: glyph_axis(glyph_row(g), self.padded_h()),
         })
     }
+
+    /// Gets whether this font supplies a glyph for `char`.
+    ///
+    /// A font supplies a glyph for every character in its atlas, and every character whose code
+    /// point falls within its first `code_point_rows` rows.
+    #[must_use]
+    pub fn has_glyph(&self, char: char) -> bool {
+        self.atlas.cell(char).is_some()
+            || char_to_ascii(char).is_some_and(|g| glyph_row(g) < self.code_point_rows)
+    }
+
+    /// The character whose glyph has its top-left at `top_left` in the font, if any.
+    ///
+    /// This is the inverse of `glyph_top_left`, and is useful for backends that draw characters
+    /// rather than textures.
+    #[must_use]
+    pub fn glyph_char(&self, top_left: Point) -> Option<char> {
+        let col: u8 = glyph_index(top_left.x, self.padded_w())?;
+        let row: u16 = glyph_index(top_left.y, self.padded_h())?;
+        if NUM_COLS <= col {
+            return None;
+        }
+
+        if let Some(char) = self.atlas.char(atlas::Cell { col, row }) {
+            return Some(char);
+        }
+
+        let code = u8::try_from(row)
+            .ok()?
+            .checked_mul(NUM_COLS)?
+            .checked_add(col)?;
+        Some(char::from(code))
+    }
+}
+
+/// Calculates one axis of a glyph's index, given the top-left coordinate on that axis.
+fn glyph_index<T: TryFrom<Length>>(coord: Length, size: Length) -> Option<T> {
+    if size <= 0 || coord % size != 0 {
+        return None;
+    }
+    T::try_from(coord / size).ok()
 }
 
 /// Calculates one axis of the top-left of the glyph.
@@ -182,6 +350,9 @@ mod tests {
             pad: Size { w: 1, h: 1 },
             width_overrides: [("iI", 1)].into_iter().collect(),
             kerning: kerning::Spec::default(),
+            atlas: [(8, "éΩ")].into_iter().collect(),
+            code_point_rows: 4,
+            ..Spec::default()
         }
         .into_metrics()
         .expect("should not fail to expand metrics")
@@ -201,10 +372,70 @@ mod tests {
         assert_eq!(big_font().glyph_top_left(char::from(255)).y, 70);
     }
 
+    /// Tests that atlas characters are placed on their atlas cells.
+    #[test]
+    fn glyph_atlas() {
+        let font = big_font();
+        assert_eq!(Point { x: 10, y: 80 }, font.glyph_top_left('Ω'));
+        // Atlas placements take priority over the code point mapping.
+        assert_eq!(Point { x: 0, y: 80 }, font.glyph_top_left('é'));
+        // Anything else beyond code point 255 falls back to the top-left.
+        assert_eq!(Point::default(), font.glyph_top_left('コ'));
+    }
+
+    /// Tests that `has_glyph` covers the atlas and the code point rows.
+    #[test]
+    fn has_glyph() {
+        let font = big_font();
+        assert!(font.has_glyph('a'));
+        assert!(font.has_glyph('Ω'));
+        assert!(font.has_glyph('é'));
+        assert!(!font.has_glyph('ö'));
+        assert!(!font.has_glyph('コ'));
+    }
+
+    /// Tests that `glyph_char` inverts `glyph_top_left`.
+    #[test]
+    fn glyph_char_round_trip() {
+        let font = big_font();
+        for c in ['a', 'Z', '~', char::from(255), 'é', 'Ω'] {
+            assert_eq!(Some(c), font.glyph_char(font.glyph_top_left(c)));
+        }
+        assert_eq!(None, font.glyph_char(Point { x: 5, y: 0 }));
+    }
+
     /// Tests that `span_w_str` appears to handle overrides properly.
     #[test]
     fn span_w_str_overrides() {
         // 3*9 normal + 2*1 overrides + 4*1 padding
         assert_eq!(big_font().span_w_str("Icing"), 33);
     }
+
+    /// Tests that scaling a spec scales widths, overrides, kerning, and padding alike.
+    #[test]
+    fn scaled_spans() {
+        let spec = Spec {
+            char: Size { w: 9, h: 9 },
+            pad: Size { w: 1, h: 1 },
+            width_overrides: [("iI", 1)].into_iter().collect(),
+            kerning: kerning::Spec {
+                left: [("l".to_owned(), "I".to_owned())].into_iter().collect(),
+                right: [("r".to_owned(), "c".to_owned())].into_iter().collect(),
+                pairs: [(("l".to_owned(), "r".to_owned()), 0)]
+                    .into_iter()
+                    .collect(),
+            },
+            ..Spec::default()
+        };
+        let metrics = |spec: Spec| {
+            spec.into_metrics()
+                .expect("should not fail to expand metrics")
+        };
+
+        // 3*9 normal + 2*1 overrides + 3*1 padding + 0 kerned
+        assert_eq!(32, metrics(spec.clone()).span_w_str("Icing"));
+        let scaled = metrics(spec.scaled(2));
+        assert_eq!(64, scaled.span_w_str("Icing"));
+        assert_eq!(20, scaled.padded_h());
+    }
 }
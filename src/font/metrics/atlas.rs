@@ -0,0 +1,148 @@
+//! Glyph atlas specifications and tables.
+//!
+//! By default, a font texture is a grid of 32 columns, and a character's position on the grid is
+//! its code point (so only code points up to 255 are reachable).  The atlas lets a font place
+//! arbitrary code points onto further cells of the grid, usually on rows below the ones covered by
+//! the default mapping.
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    super::{Error, Result},
+    chars::Subtable,
+    NUM_COLS,
+};
+
+/// A row-based specification of atlas placements.
+///
+/// Each entry maps a row of the texture grid to a string of characters; the characters are placed
+/// on that row from left to right, starting at the first column.
+///
+/// If a character appears more than once, its last (lowest) placement wins.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Spec(BTreeMap<u16, String>);
+
+/// We can construct a [Spec] by iterating over row/string pairs.
+impl<S: ToString> FromIterator<(u16, S)> for Spec {
+    fn from_iter<T: IntoIterator<Item = (u16, S)>>(iter: T) -> Self {
+        Spec(
+            iter.into_iter()
+                .map(|(row, chars)| (row, chars.to_string()))
+                .collect(),
+        )
+    }
+}
+
+impl Spec {
+    /// Compiles this specification into an atlas table.
+    ///
+    /// # Errors
+    ///
+    /// Fails if any row has more characters than there are columns in the grid.
+    pub fn into_table(self) -> Result<Table> {
+        let mut table = Table::default();
+
+        for (row, chars) in self.0 {
+            let len = chars.chars().count();
+            if usize::from(NUM_COLS) < len {
+                return Err(Error::AtlasRowOverflow { row, len });
+            }
+
+            for (col, char) in (0..NUM_COLS).zip(chars.chars()) {
+                table.insert(char, Cell { col, row });
+            }
+        }
+
+        Ok(table)
+    }
+}
+
+/// A cell in the texture grid.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Cell {
+    /// The column of the cell.
+    pub col: u8,
+    /// The row of the cell.
+    pub row: u16,
+}
+
+/// A compiled atlas, mapping characters to cells and back.
+#[derive(Clone, Debug, Default)]
+pub struct Table {
+    cells: Subtable<Cell>,
+    chars: HashMap<Cell, char>,
+}
+
+impl Table {
+    /// Gets the cell for `char`, if it is in the atlas.
+    #[must_use]
+    pub fn cell(&self, char: char) -> Option<Cell> {
+        self.cells.get(char).copied()
+    }
+
+    /// Iterates over every character in the atlas, in no particular order.
+    pub fn chars(&self) -> impl Iterator<Item = char> + '_ {
+        self.chars.values().copied()
+    }
+
+    /// Gets the character placed on `cell`, if any.
+    #[must_use]
+    pub fn char(&self, cell: Cell) -> Option<char> {
+        self.chars.get(&cell).copied()
+    }
+
+    fn insert(&mut self, char: char, cell: Cell) {
+        if let Some(old) = self.cell(char) {
+            self.chars.remove(&old);
+        }
+        self.cells.insert(char, cell);
+        self.chars.insert(cell, char);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Characters are placed left-to-right on their rows.
+    #[test]
+    fn into_table_places() {
+        let table = [(8, "éöß"), (9, "Ωコ")]
+            .into_iter()
+            .collect::<Spec>()
+            .into_table()
+            .expect("spec should be valid");
+
+        assert_eq!(Some(Cell { col: 0, row: 8 }), table.cell('é'));
+        assert_eq!(Some(Cell { col: 2, row: 8 }), table.cell('ß'));
+        assert_eq!(Some(Cell { col: 1, row: 9 }), table.cell('コ'));
+        assert_eq!(Some('ö'), table.char(Cell { col: 1, row: 8 }));
+        assert_eq!(None, table.cell('a'));
+    }
+
+    /// Later placements of the same character override earlier ones.
+    #[test]
+    fn into_table_duplicate() {
+        let table = [(8, "ab"), (9, "b")]
+            .into_iter()
+            .collect::<Spec>()
+            .into_table()
+            .expect("spec should be valid");
+
+        assert_eq!(Some(Cell { col: 0, row: 9 }), table.cell('b'));
+        assert_eq!(None, table.char(Cell { col: 1, row: 8 }));
+    }
+
+    /// Rows can't hold more characters than there are columns.
+    #[test]
+    fn into_table_overflow() {
+        let spec: Spec = [(8, "x".repeat(33))].into_iter().collect();
+        assert!(matches!(
+            spec.into_table(),
+            Err(Error::AtlasRowOverflow { row: 8, len: 33 })
+        ));
+    }
+}
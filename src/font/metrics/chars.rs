@@ -54,6 +54,21 @@ impl Table {
             default,
         })
     }
+
+    /// Constructs a character table where every character has the same width and kerning.
+    ///
+    /// This is useful for monospace fonts.
+    #[must_use]
+    pub fn uniform(width: Length, kerning: Length) -> Self {
+        Self {
+            entries: Subtable::new(),
+            default: Entry {
+                width,
+                rights: None,
+                default_kerning: kerning,
+            },
+        }
+    }
 }
 
 fn add_kerning(table: &mut Subtable<Entry>, kerning: kerning::Map, default: &Entry) {
@@ -3,7 +3,7 @@
 //! The general class-based kerning approach here is vaguely similar to `OpenType` class-based
 //! kerning: we have a left-table, a right-table, and pairwise adjustments between them.
 
-use std::collections::{BTreeMap, HashMap};
+use std::collections::BTreeMap;
 
 use serde::{Deserialize, Serialize};
 
@@ -13,12 +13,12 @@ use crate::metrics::Length;
 pub type Class = String;
 
 /// A map containing kerning classes (mapping from identifiers to character sets).
-pub type ClassTable = HashMap<Class, String>;
+pub type ClassTable = BTreeMap<Class, String>;
 
 /// A map providing spacing overrides for pairs.
 ///
 /// Note that these are *absolute* spaces, not adjustments.  This may change.
-pub type PairTable = HashMap<(Class, Class), Length>;
+pub type PairTable = BTreeMap<(Class, Class), Length>;
 
 /// A complete kerning specification.
 ///
@@ -35,7 +35,82 @@ pub struct Spec {
     pub pairs: PairTable,
 }
 
+/// We can construct a [Spec] by iterating over left/right/spacing triples.
+///
+/// Characters on the right of pairs share a class whenever they have the same spacing after every
+/// character, and then characters on the left share a class whenever they have the same spacing
+/// before every right class.  Each class is named after its characters.  If a pair appears more
+/// than once, its last spacing wins.
+///
+/// This keeps the classes small enough to review by hand, but isn't guaranteed to find the fewest
+/// classes (or pairs) that keep every pair's spacing.
+///
+/// # Example
+///
+/// ```
+/// use ugly::font::metrics::kerning::Spec;
+///
+/// let spec: Spec = [
+///     ('T', 'a', -1),
+///     ('T', 'o', -1),
+///     ('V', 'a', -1),
+///     ('V', 'o', -1),
+///     ('V', 'e', -1),
+///     ('W', 'e', -1),
+/// ]
+/// .into_iter()
+/// .collect();
+/// assert_eq!(vec!["ao", "e"], spec.right.keys().collect::<Vec<_>>());
+/// assert_eq!(vec!["T", "V", "W"], spec.left.keys().collect::<Vec<_>>());
+/// assert_eq!(4, spec.pairs.len());
+/// assert_eq!(Some(&-1), spec.pairs.get(&("V".to_owned(), "ao".to_owned())));
+/// ```
+impl FromIterator<(char, char, Length)> for Spec {
+    fn from_iter<T: IntoIterator<Item = (char, char, Length)>>(iter: T) -> Self {
+        // The spacing of each right character after each left character.
+        let mut columns: BTreeMap<char, BTreeMap<char, Length>> = BTreeMap::new();
+        for (l, r, spacing) in iter {
+            columns.entry(r).or_default().insert(l, spacing);
+        }
+        let mut rights: BTreeMap<BTreeMap<char, Length>, String> = BTreeMap::new();
+        for (r, column) in columns {
+            rights.entry(column).or_default().push(r);
+        }
+
+        // The spacing of each left character before each right class.
+        let mut rows: BTreeMap<char, BTreeMap<&str, Length>> = BTreeMap::new();
+        for (column, rs) in &rights {
+            for (&l, &spacing) in column {
+                rows.entry(l).or_default().insert(rs, spacing);
+            }
+        }
+        let mut lefts: BTreeMap<BTreeMap<&str, Length>, String> = BTreeMap::new();
+        for (l, row) in rows {
+            lefts.entry(row).or_default().push(l);
+        }
+
+        let mut spec = Spec::default();
+        for (row, ls) in lefts {
+            for (rs, spacing) in row {
+                spec.right.insert(rs.to_owned(), rs.to_owned());
+                spec.pairs.insert((ls.clone(), rs.to_owned()), spacing);
+            }
+            spec.left.insert(ls.clone(), ls);
+        }
+        spec
+    }
+}
+
 impl Spec {
+    /// Multiplies every pair spacing in this spec by `factor`.
+    #[must_use]
+    pub fn scaled(mut self, factor: Length) -> Self {
+        for spacing in self.pairs.values_mut() {
+            *spacing *= factor;
+        }
+        self
+    }
+
     /// Compiles this specification into a full kerning pairs map.
     ///
     /// # Errors
@@ -1,6 +1,6 @@
 //! Width override specifications and tables.
 
-use std::collections::{BTreeMap, HashMap};
+use std::collections::BTreeMap;
 
 use serde::{Deserialize, Serialize};
 
@@ -9,7 +9,7 @@ use super::super::{super::metrics::Length, Error, Result};
 /// A class-based specification of width overrides.
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 #[serde(transparent)]
-pub struct Spec(HashMap<String, Length>);
+pub struct Spec(BTreeMap<String, Length>);
 
 /// We can construct a [Spec] by iterating over class/length pairs.
 impl<S: ToString> FromIterator<(S, Length)> for Spec {
@@ -33,6 +33,17 @@ impl Spec {
         Ok(self.expand_map())
     }
 
+    /// Multiplies every override in this spec by `factor`.
+    #[must_use]
+    pub fn scaled(self, factor: Length) -> Self {
+        Spec(
+            self.0
+                .into_iter()
+                .map(|(class, l)| (class, l * factor))
+                .collect(),
+        )
+    }
+
     /// Expands the overrides in this spec.
     fn expand_map(self) -> BTreeMap<char, Length> {
         self.0
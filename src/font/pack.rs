@@ -0,0 +1,128 @@
+//! Packed fonts: a font's metrics and texture in one file.
+//!
+//! A packed font is the magic bytes `UGLYFONT`, then the length in bytes of the metrics (as a
+//! little-endian `u32`), then the metrics themselves (as RON), then the texture (as PNG), which
+//! runs to the end of the file.  Packed fonts can be embedded into programs with `include_bytes!`
+//! and loaded with [`Font::from_packed`](super::Font::from_packed), so that programs don't need a
+//! font directory alongside them.
+
+use super::{metrics::Spec, Result};
+
+/// The bytes that start every packed font.
+pub const MAGIC: [u8; 8] = *b"UGLYFONT";
+
+/// Enumeration of possible errors when reading or writing packed fonts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum Error {
+    /// The data doesn't start with the packed font magic bytes.
+    #[error("Not a packed font")]
+    BadMagic,
+    /// The data ends before the metrics do.
+    #[error("Packed font ended unexpectedly")]
+    Truncated,
+    /// The metrics aren't valid UTF-8.
+    #[error("Packed font metrics aren't UTF-8")]
+    BadMetrics,
+    /// The metrics are too long to pack.
+    #[error("Metrics too long to pack ({0} bytes)")]
+    Oversized(usize),
+}
+
+/// Packs RON `metrics` and a PNG texture, `png`, into a single packed font.
+///
+/// # Errors
+///
+/// Fails if the metrics are more than 4GiB long.
+pub fn pack(metrics: &str, png: &[u8]) -> Result<Vec<u8>> {
+    let len = u32::try_from(metrics.len()).map_err(|_| Error::Oversized(metrics.len()))?;
+
+    let mut bytes = Vec::with_capacity(MAGIC.len() + 4 + metrics.len() + png.len());
+    bytes.extend_from_slice(&MAGIC);
+    bytes.extend_from_slice(&len.to_le_bytes());
+    bytes.extend_from_slice(metrics.as_bytes());
+    bytes.extend_from_slice(png);
+    Ok(bytes)
+}
+
+/// Packs a metrics `spec` and `texture` into a single packed font.
+///
+/// # Errors
+///
+/// Fails if the metrics can't be serialised, or the texture can't be encoded as a PNG.
+pub fn pack_spec(spec: &Spec, texture: &image::RgbaImage) -> Result<Vec<u8>> {
+    let metrics = ron::ser::to_string_pretty(spec, ron::ser::PrettyConfig::default())?;
+
+    let mut png = std::io::Cursor::new(vec![]);
+    texture
+        .write_to(&mut png, image::ImageFormat::Png)
+        .map_err(|e| super::Error::TextureSave(e.to_string()))?;
+    pack(&metrics, png.get_ref())
+}
+
+/// Splits a packed font into its RON metrics and its PNG texture.
+///
+/// The texture isn't checked here; it is just whatever follows the metrics.
+///
+/// # Errors
+///
+/// Fails if the data isn't a packed font, or the metrics are cut short or aren't UTF-8.
+pub fn unpack(bytes: &[u8]) -> Result<(&str, &[u8])> {
+    let rest = bytes.strip_prefix(&MAGIC).ok_or(Error::BadMagic)?;
+    let (len, rest) = rest.split_first_chunk::<4>().ok_or(Error::Truncated)?;
+    let len = usize::try_from(u32::from_le_bytes(*len)).map_err(|_| Error::Truncated)?;
+    if rest.len() < len {
+        return Err(Error::Truncated.into());
+    }
+
+    let (metrics, png) = rest.split_at(len);
+    let metrics = std::str::from_utf8(metrics).map_err(|_| Error::BadMetrics)?;
+    Ok((metrics, png))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tests that packed fonts split back into their parts.
+    #[test]
+    fn pack_round_trip() {
+        let bytes = pack("(char: (w: 3, h: 4))", b"\x89PNG").expect("should pack");
+        assert_eq!(b"UGLYFONT\x14\0\0\0(char", &bytes[..17]);
+
+        let (metrics, png) = unpack(&bytes).expect("should unpack");
+        assert_eq!("(char: (w: 3, h: 4))", metrics);
+        assert_eq!(b"\x89PNG", png);
+    }
+
+    /// Tests that ill-formed packed fonts are rejected.
+    #[test]
+    fn unpack_errors() {
+        let err = |bytes: &[u8]| match unpack(bytes) {
+            Err(super::super::Error::Pack(e)) => Some(e),
+            _ => None,
+        };
+        assert_eq!(Some(Error::BadMagic), err(b"font.png"));
+        assert_eq!(Some(Error::Truncated), err(b"UGLYFONT\x01"));
+        assert_eq!(Some(Error::Truncated), err(b"UGLYFONT\x04\0\0\0()"));
+        assert_eq!(Some(Error::BadMetrics), err(b"UGLYFONT\x01\0\0\0\xFF"));
+    }
+
+    /// Tests loading fonts, at a scale, from packed fonts in memory.
+    #[test]
+    fn font_from_packed() {
+        let spec = Spec {
+            char: crate::metrics::Size { w: 3, h: 4 },
+            ..Spec::default()
+        };
+        let texture = image::RgbaImage::new(96, 8);
+        let bytes = pack_spec(&spec, &texture).expect("should pack");
+
+        let font = super::super::Font::from_packed(bytes).scaled(2);
+        assert_eq!(6, font.metrics().expect("should load metrics").char.w);
+        assert_eq!(
+            (192, 16),
+            font.texture().expect("should load texture").dimensions()
+        );
+        assert_eq!(None, font.texture_path());
+    }
+}
@@ -4,8 +4,8 @@
 //! This is primarily to avoid unnecessary casting and potential overflow/underflow corner cases.
 //!
 //! `ugly` itself doesn't specify the units used for lengths; they are backend-dependent.
-//! In SDL2, for instance, they are logical pixel values.  Future text-based backends may instead
-//! specify character cell units.
+//! In SDL2, for instance, they are logical pixel values.  The text-based `terminal` backend
+//! instead uses character cell units.
 
 pub mod anchor;
 pub mod axis;
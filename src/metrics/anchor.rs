@@ -27,18 +27,48 @@ impl Anchor {
         y: Y::Top,
     };
 
+    /// Top-centre anchoring.
+    pub const TOP_CENTRE: Self = Anchor {
+        x: X::Centre,
+        y: Y::Top,
+    };
+
     /// Top-right anchoring.
     pub const TOP_RIGHT: Self = Anchor {
         x: X::Right,
         y: Y::Top,
     };
 
+    /// Centre-left anchoring.
+    pub const CENTRE_LEFT: Self = Anchor {
+        x: X::Left,
+        y: Y::Centre,
+    };
+
+    /// Centre anchoring.
+    pub const CENTRE: Self = Anchor {
+        x: X::Centre,
+        y: Y::Centre,
+    };
+
+    /// Centre-right anchoring.
+    pub const CENTRE_RIGHT: Self = Anchor {
+        x: X::Right,
+        y: Y::Centre,
+    };
+
     /// Bottom-left anchoring.
     pub const BOTTOM_LEFT: Self = Anchor {
         x: X::Left,
         y: Y::Bottom,
     };
 
+    /// Bottom-centre anchoring.
+    pub const BOTTOM_CENTRE: Self = Anchor {
+        x: X::Centre,
+        y: Y::Bottom,
+    };
+
     /// Bottom-right anchoring.
     pub const BOTTOM_RIGHT: Self = Anchor {
         x: X::Right,
@@ -92,8 +122,15 @@ pub enum Y {
     /// left-to-right languages naturally proceeds from the top-left.
     #[default]
     Top,
+    /// Anchoring to the centre.
+    Centre,
     /// Anchoring to the bottom edge.
     Bottom,
+    /// Anchoring to the baseline of the first line of text.
+    ///
+    /// This lets text in different fonts line up along a common baseline.  Anything that isn't
+    /// text, such as a plain rectangle, has its baseline on its bottom edge.
+    Baseline,
 }
 
 impl Y {
@@ -105,13 +142,34 @@ impl Y {
     /// use ugly::metrics::anchor;
     ///
     /// assert_eq!(0, anchor::Y::Top.offset(240));
+    /// assert_eq!(120, anchor::Y::Centre.offset(240));
     /// assert_eq!(240, anchor::Y::Bottom.offset(240));
+    /// assert_eq!(240, anchor::Y::Baseline.offset(240));
     /// ```
     #[must_use]
     pub fn offset(self, height: i32) -> i32 {
+        self.offset_with_baseline(height, height)
+    }
+
+    /// Like `offset`, but for an object whose baseline is `baseline` pixels from its top.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ugly::metrics::anchor;
+    ///
+    /// assert_eq!(0, anchor::Y::Top.offset_with_baseline(16, 12));
+    /// assert_eq!(8, anchor::Y::Centre.offset_with_baseline(16, 12));
+    /// assert_eq!(16, anchor::Y::Bottom.offset_with_baseline(16, 12));
+    /// assert_eq!(12, anchor::Y::Baseline.offset_with_baseline(16, 12));
+    /// ```
+    #[must_use]
+    pub fn offset_with_baseline(self, height: i32, baseline: i32) -> i32 {
         match self {
             Self::Top => 0,
+            Self::Centre => height / 2,
             Self::Bottom => height,
+            Self::Baseline => baseline,
         }
     }
 }
@@ -81,6 +81,8 @@ impl Rect {
     /// let rect = Rect::new(20, 55, 25, 15);
     /// assert_eq!(Point{x: 20, y: 55}, rect.anchor(Anchor::TOP_LEFT));
     /// assert_eq!(Point{x: 45, y: 55}, rect.anchor(Anchor::TOP_RIGHT));
+    /// assert_eq!(Point{x: 32, y: 62}, rect.anchor(Anchor::CENTRE));
+    /// assert_eq!(Point{x: 20, y: 62}, rect.anchor(Anchor::CENTRE_LEFT));
     /// assert_eq!(Point{x: 20, y: 70}, rect.anchor(Anchor::BOTTOM_LEFT));
     /// assert_eq!(Point{x: 45, y: 70}, rect.anchor(Anchor::BOTTOM_RIGHT));
     /// ```
@@ -9,12 +9,23 @@ use super::{error, font, metrics};
 /// The trait is parameterised by the specific maps used to look up font metrics and colours
 /// in the application.
 pub trait Renderer<FontId, FgId, BgId> {
-    /// Writes the layout-calculated string `str` with the font `font` and foreground colour `fg`.
+    /// Writes the layout-calculated string `str` with the font chain `fonts` and foreground colour
+    /// `fg`, offset by `offset` from the top-left of its bounds.
+    ///
+    /// Each glyph is drawn with the font at its index in `fonts`; glyphs whose index is outside
+    /// the chain are skipped.  Any decorations in `str` are filled with `fg`, where the renderer
+    /// can draw them.
     ///
     /// # Errors
     ///
     /// Fails if the renderer can't render the writing.
-    fn write(&mut self, font: FontId, fg: FgId, str: &font::layout::String) -> error::Result<()>;
+    fn write(
+        &mut self,
+        fonts: &font::Chain<FontId>,
+        fg: FgId,
+        str: &font::layout::String,
+        offset: metrics::point::Delta,
+    ) -> error::Result<()>;
 
     /// Fills the rectangle `rect`, whose top-left is positioned relative to
     /// the current position, with the background colour `bg`.
@@ -10,7 +10,7 @@ use super::Renderer;
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub enum Command<FontId, FgId, BgId> {
     /// Represents a `write` command.
-    Write(FontId, FgId, font::layout::String),
+    Write(font::Chain<FontId>, FgId, font::layout::String),
     /// Represents a `fill` command.
     Fill(metrics::Rect, BgId),
     /// Represents a `clear` command.
@@ -32,8 +32,17 @@ impl<FontId, FgId, BgId> Renderer<FontId, FgId, BgId> for Logger<FontId, FgId, B
 where
     FontId: Default + Eq + Hash + Copy + Clone,
 {
-    fn write(&mut self, font: FontId, fg: FgId, str: &font::layout::String) -> crate::Result<()> {
-        self.log.push(Command::Write(font, fg, str.clone()));
+    fn write(
+        &mut self,
+        fonts: &font::Chain<FontId>,
+        fg: FgId,
+        str: &font::layout::String,
+        offset: metrics::point::Delta,
+    ) -> crate::Result<()> {
+        // The log records where the string was drawn, so we move a copy of it by the offset.
+        let mut str = str.clone();
+        str.bounds.top_left = str.bounds.top_left.offset(offset.dx, offset.dy);
+        self.log.push(Command::Write(fonts.clone(), fg, str));
         Ok(())
     }
 
@@ -1,5 +1,11 @@
 //! Mid-level text composition interface.
 
+pub mod effect;
+pub mod options;
+pub mod rich;
+
+pub use options::Options;
+
 use std::mem;
 
 use crate::{error, font, metrics, render, resource::Map};
@@ -11,18 +17,18 @@ use crate::{error, font, metrics, render, resource::Map};
 /// options change.
 #[derive(Debug, Clone)]
 pub struct Writer<FontId, FgId> {
-    /// The point used as the anchor for the writing.
-    pos: metrics::Point,
+    /// The options for positioning and laying out the writing.
+    options: Options,
 
-    /// The alignment for the writing.
-    alignment: metrics::anchor::X,
-
-    /// The font being used for writing.
-    pub(crate) font: FontId,
+    /// The font chain being used for writing.
+    pub(crate) fonts: font::Chain<FontId>,
 
     /// The foreground colour being used for writing.
     fg: FgId,
 
+    /// The effects applied to the writing.
+    effects: effect::Effects<FgId>,
+
     /// The most recently laid-out string.
     layout: font::layout::String,
 
@@ -44,10 +50,10 @@ impl<FontId, FgId> Writer<FontId, FgId> {
     /// Constructs a new writer with the given font and colours.
     pub fn new(font: FontId, fg: FgId) -> Self {
         Self {
-            pos: metrics::Point::default(),
-            alignment: metrics::anchor::X::Left,
-            font,
+            options: Options::default(),
+            fonts: font::Chain::new(font),
             fg,
+            effects: effect::Effects::default(),
             layout: font::layout::String::default(),
             layout_reusable: false,
         }
@@ -66,22 +72,54 @@ where
     pub fn layout(&mut self, metrics: &impl Map<font::Metrics, Id = FontId>) {
         // Optimistically assume that the next time we call `layout`, everything will be the same.
         let reusable = mem::replace(&mut self.layout_reusable, true);
-        if !reusable {
+        let change = self.options.take_change();
+        if !reusable || change == options::Change::Layout {
             self.actually_layout(metrics);
+        } else if change == options::Change::Position {
+            self.reposition_layout();
         }
     }
 
+    /// Measures the size the current string would have if laid out using `metrics`.
+    ///
+    /// This honours the options of the writer, including line height and paragraph spacing, and
+    /// any widening from its effects, but doesn't change the current layout.
+    pub fn measure(&self, metrics: &impl Map<font::Metrics, Id = FontId>) -> metrics::Size {
+        self.effects
+            .widen(self.builder(metrics).dry_run(&self.layout.string).size)
+    }
+
+    /// Measures the size the current string would have if laid out using `metrics` and wrapped to
+    /// `max_width`, in place of any maximum width set on the writer.
+    pub fn measure_wrapped(
+        &self,
+        metrics: &impl Map<font::Metrics, Id = FontId>,
+        max_width: metrics::Length,
+    ) -> metrics::Size {
+        let builder = self.builder(metrics).with_max_width(max_width);
+        self.effects
+            .widen(builder.dry_run(&self.layout.string).size)
+    }
+
     /// Lays out `str` using `metrics`.
     fn actually_layout(&mut self, metrics: &impl Map<font::Metrics, Id = FontId>) {
-        let fm = metrics.get(self.font);
-
         let current_string = mem::take(&mut self.layout);
-
-        self.layout = font::layout::Builder::new(fm)
-            .with_alignment(self.alignment)
-            .build(current_string.string);
+        self.layout = self.builder(metrics).build(current_string.string);
+        self.layout.bounds.size = self.effects.widen(self.layout.bounds.size);
         self.reposition_layout();
     }
+
+    /// Makes a layout builder for the font chain and options of this writer.
+    fn builder<'a>(
+        &'a self,
+        metrics: &'a impl Map<font::Metrics, Id = FontId>,
+    ) -> font::layout::Builder<'a> {
+        let mut builder = font::layout::Builder::new(metrics.get(self.fonts.primary()));
+        for fallback in self.fonts.fallbacks() {
+            builder = builder.with_fallback(metrics.get(*fallback));
+        }
+        self.options.configure(builder)
+    }
 }
 
 impl<FontId, FgId> Writer<FontId, FgId>
@@ -91,6 +129,9 @@ where
 {
     /// Renders the most recently written string.
     ///
+    /// Any effects are rendered by writing the string several times, at different offsets and in
+    /// different colours.
+    ///
     /// # Errors
     ///
     /// Fails if the renderer can't blit glyphs to the screen.
@@ -98,42 +139,161 @@ where
         &self,
         r: &mut impl render::Renderer<FontId, FgId, BgId>,
     ) -> error::Result<()> {
-        r.write(self.font, self.fg, &self.layout)
+        if self.effects.is_empty() {
+            return r.write(
+                &self.fonts,
+                self.fg,
+                &self.layout,
+                metrics::point::Delta::default(),
+            );
+        }
+
+        for (delta, fg) in self.effects.passes(self.fg) {
+            r.write(&self.fonts, fg, &self.layout, delta)?;
+        }
+        Ok(())
     }
 }
 
 impl<FontId, FgId> Writer<FontId, FgId> {
+    /// Gets the positioning and layout options of this writer.
+    pub fn options(&self) -> &Options {
+        &self.options
+    }
+
+    /// Gets the positioning and layout options of this writer, for changing them.
+    ///
+    /// Changes take effect the next time this writer is laid out.
+    pub fn options_mut(&mut self) -> &mut Options {
+        &mut self.options
+    }
+
+    /// Gets the position of this writer.
+    #[must_use]
+    pub fn pos(&self) -> metrics::Point {
+        self.options.pos()
+    }
+
+    /// Sets the position of this writer to `pos`.
+    pub fn move_to(&mut self, pos: metrics::Point) {
+        self.options.move_to(pos);
+    }
+
     /// Gets the alignment of this writer.
+    #[must_use]
     pub fn alignment(&self) -> metrics::anchor::X {
-        self.alignment
+        self.options.alignment()
     }
 
     /// Sets the alignment of this writer to `alignment`.
     pub fn align_to(&mut self, alignment: metrics::anchor::X) {
-        if self.alignment != alignment {
-            self.alignment = alignment;
+        self.options.align_to(alignment);
+    }
 
-            // TODO(@MattWindsor91): we should be able to reuse the layout by shifting the glyphs.
-            self.layout_reusable = false;
-        }
+    /// Gets the vertical alignment of this writer.
+    #[must_use]
+    pub fn vertical_alignment(&self) -> metrics::anchor::Y {
+        self.options.vertical_alignment()
     }
 
-    /// Gets the position of this writer.
-    pub fn pos(&self) -> metrics::Point {
-        self.pos
+    /// Sets the vertical alignment of this writer to `alignment`.
+    pub fn valign_to(&mut self, alignment: metrics::anchor::Y) {
+        self.options.valign_to(alignment);
     }
 
-    /// Sets the position of this writer to `pos`.
-    pub fn move_to(&mut self, pos: metrics::Point) {
-        if self.pos != pos {
-            self.pos = pos;
-            self.reposition_layout();
-        }
+    /// Gets the alignment of each line within this writer's text, if set separately.
+    #[must_use]
+    pub fn line_alignment(&self) -> Option<font::layout::LineAlignment> {
+        self.options.line_alignment()
+    }
+
+    /// Sets the alignment of each line within this writer's text to `alignment`.
+    pub fn set_line_alignment(&mut self, alignment: Option<font::layout::LineAlignment>) {
+        self.options.set_line_alignment(alignment);
+    }
+
+    /// Gets the distance between the tops of consecutive lines of this writer.
+    #[must_use]
+    pub fn line_height(&self) -> font::layout::LineHeight {
+        self.options.line_height()
+    }
+
+    /// Sets the distance between the tops of consecutive lines of this writer to `line_height`.
+    pub fn set_line_height(&mut self, line_height: font::layout::LineHeight) {
+        self.options.set_line_height(line_height);
+    }
+
+    /// Gets the extra space between paragraphs of this writer.
+    #[must_use]
+    pub fn paragraph_spacing(&self) -> metrics::Length {
+        self.options.paragraph_spacing()
+    }
+
+    /// Sets the extra space between paragraphs of this writer to `spacing`.
+    pub fn set_paragraph_spacing(&mut self, spacing: metrics::Length) {
+        self.options.set_paragraph_spacing(spacing);
+    }
+
+    /// Gets the width at which this writer wraps text, if any.
+    #[must_use]
+    pub fn max_width(&self) -> Option<metrics::Length> {
+        self.options.max_width()
     }
 
-    /// Sets the font of this writer to `id`.
+    /// Sets the width at which this writer wraps text at word boundaries to `max_width`.
+    pub fn wrap_to(&mut self, max_width: Option<metrics::Length>) {
+        self.options.wrap_to(max_width);
+    }
+
+    /// Sets how this writer wraps words too long to fit within its maximum width.
+    pub fn set_long_words(&mut self, long_words: font::layout::LongWords) {
+        self.options.set_long_words(long_words);
+    }
+
+    /// Gets how this writer truncates text wider than its maximum width, if it does.
+    #[must_use]
+    pub fn truncation(&self) -> Option<&font::layout::Truncation> {
+        self.options.truncation()
+    }
+
+    /// Sets whether, and how, this writer truncates text wider than its maximum width.
+    pub fn set_truncation(&mut self, truncation: Option<font::layout::Truncation>) {
+        self.options.set_truncation(truncation);
+    }
+
+    /// Sets the tab stops of this writer.
+    pub fn set_tab_stops(&mut self, tab_stops: Option<font::layout::TabStops>) {
+        self.options.set_tab_stops(tab_stops);
+    }
+
+    /// Gets the decorations laid out by this writer.
+    #[must_use]
+    pub fn decorations(&self) -> font::layout::Decorations {
+        self.options.decorations()
+    }
+
+    /// Sets the decorations, such as underlines, laid out by this writer to `decorations`.
+    pub fn set_decorations(&mut self, decorations: font::layout::Decorations) {
+        self.options.set_decorations(decorations);
+    }
+
+    /// Sets the primary font of this writer to `id`, keeping any fallback fonts.
     pub fn set_font(&mut self, font: FontId) {
-        self.font = font;
+        self.fonts.set_primary(font);
+    }
+
+    /// Sets the whole font chain of this writer to `fonts`.
+    ///
+    /// Glyphs missing from the primary font will be taken from the first fallback that has them.
+    pub fn set_fonts(&mut self, fonts: impl Into<font::Chain<FontId>>)
+    where
+        FontId: PartialEq,
+    {
+        let fonts = fonts.into();
+        if self.fonts != fonts {
+            self.fonts = fonts;
+            self.layout_reusable = false;
+        }
     }
 
     /// Sets the foreground colour of this writer to `fg`.
@@ -141,6 +301,22 @@ impl<FontId, FgId> Writer<FontId, FgId> {
         self.fg = fg;
     }
 
+    /// Gets the effects applied by this writer.
+    pub fn effects(&self) -> &effect::Effects<FgId> {
+        &self.effects
+    }
+
+    /// Sets the effects applied by this writer to `effects`.
+    ///
+    /// Faux-bold widens the layout's bounds to cover the extra pixel it draws, but other effects
+    /// that draw outside the glyphs (such as outlines and shadows) can draw outside the bounds.
+    pub fn set_effects(&mut self, effects: effect::Effects<FgId>) {
+        if effects.bold != self.effects.bold {
+            self.layout_reusable = false;
+        }
+        self.effects = effects;
+    }
+
     /// Sets the string-to-be-rendered to `str`.
     pub fn set_string(&mut self, str: &(impl ToString + ?Sized)) {
         // Store the new string inside the layout; we'll recompute the rest in a bit.
@@ -151,17 +327,9 @@ impl<FontId, FgId> Writer<FontId, FgId> {
 
     /// Moves the string layout to the correct position.
     fn reposition_layout(&mut self) {
-        self.layout.bounds.top_left = self.pos;
-
-        // No point doing offsets if the anchor is left; the offset would be 0.
-        if let metrics::anchor::X::Left = self.alignment {
-            return;
-        }
-
-        // `self.alignment.offset` is the number of pixels between the left and the anchor, so we
-        // need to move so that the position (which is currently the left) is *on* that anchor.
-        // This means the offset must be backwards.
-        self.layout.bounds.top_left.x -= self.alignment.offset(self.layout.bounds.size.w);
+        self.layout.bounds.top_left = self
+            .options
+            .top_left(self.layout.bounds.size, self.layout.baseline);
     }
 }
 
@@ -169,6 +337,7 @@ impl<FontId, FgId> Writer<FontId, FgId> {
 mod tests {
     use super::*;
     use crate::{
+        metrics,
         render::{logger, Renderer},
         resource::DefaultingHashMap,
     };
@@ -200,7 +369,7 @@ mod tests {
         r.present();
 
         for c in r.log.drain(0..) {
-            if let logger::Command::Write(_, _, s) = c {
+            if let logger::Command::Write(_, (), s) = c {
                 assert_eq!(s.string, "hello, world");
                 assert_eq!(s.bounds.top_left, tl1);
             }
@@ -217,10 +386,112 @@ mod tests {
         r.present();
 
         for c in r.log.drain(0..) {
-            if let logger::Command::Write(_, _, s) = c {
+            if let logger::Command::Write(_, (), s) = c {
                 assert_eq!(s.string, "how's it going?");
                 assert_eq!(s.bounds.top_left, tl2);
             }
         }
     }
+
+    /// Tests that baseline-aligned writers in different fonts line up on their baselines.
+    #[test]
+    fn valign_baseline() {
+        let font = |h, baseline| {
+            font::metrics::Spec {
+                char: metrics::Size { w: 4, h },
+                baseline: Some(baseline),
+                ..font::metrics::Spec::default()
+            }
+            .into_metrics()
+            .expect("should not fail to expand metrics")
+        };
+        let metrics = DefaultingHashMap::new(HashMap::from([(1, font(16, 12))]), font(7, 5));
+
+        let pos = metrics::Point { x: 0, y: 20 };
+        let tops: Vec<_> = [0, 1]
+            .into_iter()
+            .map(|font| {
+                let mut writer = Writer::new(font, ());
+                writer.valign_to(metrics::anchor::Y::Baseline);
+                writer.move_to(pos);
+                writer.set_string("x");
+                writer.layout(&metrics);
+                writer.layout.bounds.top_left.y
+            })
+            .collect();
+
+        assert_eq!(vec![15, 8], tops);
+    }
+
+    /// Tests that measuring a writer honours its line height and paragraph spacing.
+    #[test]
+    fn measure_spacing() {
+        let metrics = DefaultingHashMap::new(
+            HashMap::<(), _>::new(),
+            crate::backends::terminal::metrics(),
+        );
+
+        let mut writer = Writer::<(), ()>::default();
+        writer.set_string("ab\ncd\n\nef");
+        assert_eq!(metrics::Size { w: 2, h: 3 }, writer.measure(&metrics));
+
+        writer.set_line_height(font::layout::LineHeight::Pixels(2));
+        writer.set_paragraph_spacing(3);
+        assert_eq!(metrics::Size { w: 2, h: 8 }, writer.measure(&metrics));
+    }
+
+    /// Tests that measuring a writer at a given width wraps regardless of the writer's own width.
+    #[test]
+    fn measure_wrapped() {
+        let metrics = DefaultingHashMap::new(
+            HashMap::<(), _>::new(),
+            crate::backends::terminal::metrics(),
+        );
+
+        let mut writer = Writer::<(), ()>::default();
+        writer.set_string("ab cd ef");
+        writer.wrap_to(Some(0));
+        assert_eq!(
+            metrics::Size { w: 5, h: 2 },
+            writer.measure_wrapped(&metrics, 5)
+        );
+        assert_eq!(
+            metrics::Size { w: 8, h: 1 },
+            writer.measure_wrapped(&metrics, 8)
+        );
+    }
+
+    /// Tests that faux-bold widens the bounds of a writer, and draws its text twice, one pixel
+    /// apart, without moving the layout.
+    #[test]
+    fn render_bold() {
+        let metrics = DefaultingHashMap::new(
+            HashMap::<(), _>::new(),
+            crate::backends::terminal::metrics(),
+        );
+
+        let mut writer = Writer::<(), ()>::default();
+        writer.set_string("abc");
+        writer.layout(&metrics);
+        writer.set_effects(effect::Effects {
+            bold: true,
+            ..effect::Effects::default()
+        });
+        assert_eq!(metrics::Size { w: 4, h: 1 }, writer.measure(&metrics));
+        writer.layout(&metrics);
+        assert_eq!(metrics::Size { w: 4, h: 1 }, writer.layout.bounds.size);
+
+        let mut r: logger::Logger<(), (), ()> = logger::Logger::default();
+        writer.render(&mut r).unwrap();
+        let xs: Vec<_> = r
+            .log
+            .iter()
+            .map(|c| match c {
+                logger::Command::Write(_, (), s) => s.bounds.top_left.x,
+                _ => panic!("unexpected command"),
+            })
+            .collect();
+        assert_eq!(vec![0, 1], xs);
+        assert_eq!(0, writer.layout.bounds.top_left.x);
+    }
 }
@@ -0,0 +1,153 @@
+//! Text effects, drawn by overdrawing the ordinary glyphs of a font.
+//!
+//! None of these effects need anything from the font beyond its usual texture: each effect is
+//! some number of extra passes of the same layout, offset and possibly recoloured.
+
+use crate::metrics::{point::Delta, Size};
+
+/// A drop shadow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Shadow<FgId> {
+    /// The offset of the shadow from the text.
+    pub offset: Delta,
+    /// The colour of the shadow.
+    pub colour: FgId,
+}
+
+/// A set of text effects.
+///
+/// The default set has no effects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Effects<FgId> {
+    /// The colour of a one-pixel outline around each glyph, if any.
+    pub outline: Option<FgId>,
+    /// A drop shadow, if any.
+    pub shadow: Option<Shadow<FgId>>,
+    /// Whether to embolden text by drawing it twice, one pixel apart horizontally.
+    pub bold: bool,
+}
+
+impl<FgId> Default for Effects<FgId> {
+    fn default() -> Self {
+        Self {
+            outline: None,
+            shadow: None,
+            bold: false,
+        }
+    }
+}
+
+impl<FgId> Effects<FgId> {
+    /// Widens `size`, the size of some laid-out text, to cover what these effects draw past it.
+    ///
+    /// Faux-bold draws one pixel to the right of the text, and so widens it.  Outlines and shadows
+    /// don't, as they draw around the text rather than as part of it.
+    #[must_use]
+    pub fn widen(&self, mut size: Size) -> Size {
+        if size.w > 0 {
+            size.w += i32::from(self.bold);
+        }
+        size
+    }
+}
+
+impl<FgId: Copy> Effects<FgId> {
+    /// Gets whether this set has no effects.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.outline.is_none() && self.shadow.is_none() && !self.bold
+    }
+
+    /// Calculates the passes needed to draw text with foreground colour `fg` and these effects.
+    ///
+    /// Each pass is an offset from the text's position and a colour, and passes should be drawn
+    /// in order: the shadow first, then the outline, then the text itself.
+    #[must_use]
+    pub fn passes(&self, fg: FgId) -> Vec<(Delta, FgId)> {
+        // Faux-bold widens everything, including the outline and shadow, by one pixel.
+        let bold = i32::from(self.bold);
+        let mut passes = vec![];
+
+        if let Some(Shadow { offset, colour }) = self.shadow {
+            // The shadow should cast from the outline, if there is one.
+            let spread = i32::from(self.outline.is_some());
+            for dy in -spread..=spread {
+                for dx in -spread..=spread + bold {
+                    passes.push((offset_by(offset, dx, dy), colour));
+                }
+            }
+        }
+
+        if let Some(colour) = self.outline {
+            for dy in -1..=1 {
+                for dx in -1..=1 + bold {
+                    // No point drawing outline where the text will be drawn.
+                    if dy != 0 || !(0..=bold).contains(&dx) {
+                        passes.push((Delta { dx, dy }, colour));
+                    }
+                }
+            }
+        }
+
+        passes.extend((0..=bold).map(|dx| (Delta { dx, dy: 0 }, fg)));
+        passes
+    }
+}
+
+fn offset_by(delta: Delta, dx: i32, dy: i32) -> Delta {
+    Delta {
+        dx: delta.dx + dx,
+        dy: delta.dy + dy,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tests that text without effects is drawn once.
+    #[test]
+    fn passes_plain() {
+        assert_eq!(vec![(Delta::default(), 0)], Effects::default().passes(0));
+    }
+
+    /// Tests that only faux-bold widens text, and only if there is any text.
+    #[test]
+    fn widen_bold() {
+        let size = Size { w: 5, h: 2 };
+        let bold = Effects {
+            outline: Some(1),
+            bold: true,
+            ..Effects::default()
+        };
+        assert_eq!(Size { w: 6, h: 2 }, bold.widen(size));
+        assert_eq!(Size::default(), bold.widen(Size::default()));
+        assert_eq!(size, Effects::<u8>::default().widen(size));
+    }
+
+    /// Tests the passes of bold, outlined, shadowed text.
+    #[test]
+    fn passes_all() {
+        let effects = Effects {
+            outline: Some(1),
+            shadow: Some(Shadow {
+                offset: Delta { dx: 2, dy: 2 },
+                colour: 2,
+            }),
+            bold: true,
+        };
+        let passes = effects.passes(0);
+
+        // The shadow and outline are both 4x3 blocks; the outline has a 2x1 hole for the text.
+        assert_eq!(12 + 10 + 2, passes.len());
+        assert!(passes[..12].iter().all(|(_, c)| *c == 2));
+        assert_eq!((Delta { dx: 1, dy: 1 }, 2), passes[0]);
+        assert!(passes[12..22]
+            .iter()
+            .all(|(d, c)| *c == 1 && *d != Delta::default()));
+        assert_eq!(
+            [(Delta::default(), 0), (Delta { dx: 1, dy: 0 }, 0)],
+            passes[22..]
+        );
+    }
+}
@@ -0,0 +1,292 @@
+//! Layout options shared by the plain and rich text writers.
+
+use crate::{
+    font::layout::{
+        Builder, Decorations, LineAlignment, LineHeight, LongWords, TabStops, Truncation,
+    },
+    metrics,
+};
+
+/// The options with which a writer positions and lays out its text.
+///
+/// Both [`super::Writer`] and [`super::rich::Writer`] own a set of these options.  Changes to them
+/// take effect the next time the writer is laid out; changing only the position, or the vertical
+/// alignment, moves the last layout without recalculating it.
+#[derive(Debug, Clone)]
+pub struct Options {
+    /// The point used as the anchor for the writing.
+    pos: metrics::Point,
+
+    /// The alignment for the writing.
+    alignment: metrics::anchor::X,
+
+    /// The vertical alignment for the writing.
+    vertical_alignment: metrics::anchor::Y,
+
+    /// The alignment of each line within the writing, if not the same as `alignment`.
+    line_alignment: Option<LineAlignment>,
+
+    /// The distance between the tops of consecutive lines of the writing.
+    line_height: LineHeight,
+
+    /// The extra space between paragraphs of the writing.
+    paragraph_spacing: metrics::Length,
+
+    /// The width at which to wrap the writing, if any.
+    max_width: Option<metrics::Length>,
+
+    /// How to wrap words longer than `max_width`.
+    long_words: LongWords,
+
+    /// How to truncate lines longer than `max_width`, if they should be truncated, not wrapped.
+    truncation: Option<Truncation>,
+
+    /// The tab stops for the writing, if tabs should move to them rather than being drawn.
+    tab_stops: Option<TabStops>,
+
+    /// The decorations laid out with the writing.
+    decorations: Decorations,
+
+    /// How much of the last layout these options have invalidated.
+    change: Change,
+}
+
+/// How much of a layout is out of date after changing its options.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(super) enum Change {
+    /// The layout is still valid.
+    #[default]
+    None,
+    /// The layout is valid, but needs moving.
+    Position,
+    /// The layout needs recalculating.
+    Layout,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            pos: metrics::Point::default(),
+            alignment: metrics::anchor::X::Left,
+            vertical_alignment: metrics::anchor::Y::Top,
+            line_alignment: None,
+            line_height: LineHeight::default(),
+            paragraph_spacing: 0,
+            max_width: None,
+            long_words: LongWords::default(),
+            truncation: None,
+            tab_stops: None,
+            decorations: Decorations::default(),
+            change: Change::None,
+        }
+    }
+}
+
+impl Options {
+    /// Gets the position of the writing.
+    #[must_use]
+    pub fn pos(&self) -> metrics::Point {
+        self.pos
+    }
+
+    /// Sets the position of the writing to `pos`.
+    pub fn move_to(&mut self, pos: metrics::Point) {
+        if self.pos != pos {
+            self.pos = pos;
+            self.invalidate(Change::Position);
+        }
+    }
+
+    /// Gets the alignment of the writing.
+    #[must_use]
+    pub fn alignment(&self) -> metrics::anchor::X {
+        self.alignment
+    }
+
+    /// Sets the alignment of the writing to `alignment`.
+    pub fn align_to(&mut self, alignment: metrics::anchor::X) {
+        if self.alignment != alignment {
+            self.alignment = alignment;
+
+            // TODO(@MattWindsor91): we should be able to reuse the layout by shifting the glyphs.
+            self.invalidate(Change::Layout);
+        }
+    }
+
+    /// Gets the vertical alignment of the writing.
+    #[must_use]
+    pub fn vertical_alignment(&self) -> metrics::anchor::Y {
+        self.vertical_alignment
+    }
+
+    /// Sets the vertical alignment of the writing to `alignment`.
+    ///
+    /// With [`metrics::anchor::Y::Centre`], the position is on the vertical centre of the whole
+    /// layout, across all lines.  With [`metrics::anchor::Y::Baseline`], the position is on the
+    /// baseline of the first line of text, and so writings with different fonts at the same
+    /// position line up.  Rich text uses the baseline of the primary font of its first span.
+    pub fn valign_to(&mut self, alignment: metrics::anchor::Y) {
+        if self.vertical_alignment != alignment {
+            self.vertical_alignment = alignment;
+            self.invalidate(Change::Position);
+        }
+    }
+
+    /// Gets the alignment of each line within the writing, if set separately.
+    #[must_use]
+    pub fn line_alignment(&self) -> Option<LineAlignment> {
+        self.line_alignment
+    }
+
+    /// Sets the alignment of each line within the writing to `alignment`.
+    ///
+    /// If `alignment` is `None`, lines are aligned in the same way as the writing as a whole.
+    pub fn set_line_alignment(&mut self, alignment: Option<LineAlignment>) {
+        if self.line_alignment != alignment {
+            self.line_alignment = alignment;
+            self.invalidate(Change::Layout);
+        }
+    }
+
+    /// Gets the distance between the tops of consecutive lines of the writing.
+    #[must_use]
+    pub fn line_height(&self) -> LineHeight {
+        self.line_height
+    }
+
+    /// Sets the distance between the tops of consecutive lines of the writing to `line_height`.
+    pub fn set_line_height(&mut self, line_height: LineHeight) {
+        if self.line_height != line_height {
+            self.line_height = line_height;
+            self.invalidate(Change::Layout);
+        }
+    }
+
+    /// Gets the extra space between paragraphs of the writing.
+    #[must_use]
+    pub fn paragraph_spacing(&self) -> metrics::Length {
+        self.paragraph_spacing
+    }
+
+    /// Sets the extra space between paragraphs (lines separated by blank lines) to `spacing`.
+    pub fn set_paragraph_spacing(&mut self, spacing: metrics::Length) {
+        if self.paragraph_spacing != spacing {
+            self.paragraph_spacing = spacing;
+            self.invalidate(Change::Layout);
+        }
+    }
+
+    /// Gets the width at which the writing wraps, if any.
+    #[must_use]
+    pub fn max_width(&self) -> Option<metrics::Length> {
+        self.max_width
+    }
+
+    /// Sets the width at which the writing wraps at word boundaries to `max_width`.
+    ///
+    /// If `max_width` is `None`, lines only break at explicit line breaks.
+    pub fn wrap_to(&mut self, max_width: Option<metrics::Length>) {
+        if self.max_width != max_width {
+            self.max_width = max_width;
+            self.invalidate(Change::Layout);
+        }
+    }
+
+    /// Sets how the writing wraps words too long to fit within its maximum width.
+    pub fn set_long_words(&mut self, long_words: LongWords) {
+        if self.long_words != long_words {
+            self.long_words = long_words;
+            self.invalidate(Change::Layout);
+        }
+    }
+
+    /// Gets how the writing truncates text wider than its maximum width, if it does.
+    #[must_use]
+    pub fn truncation(&self) -> Option<&Truncation> {
+        self.truncation.as_ref()
+    }
+
+    /// Sets whether, and how, the writing truncates text wider than its maximum width.
+    ///
+    /// If `truncation` is `Some`, lines wider than the maximum width are truncated rather than
+    /// wrapped.
+    pub fn set_truncation(&mut self, truncation: Option<Truncation>) {
+        if self.truncation != truncation {
+            self.truncation = truncation;
+            self.invalidate(Change::Layout);
+        }
+    }
+
+    /// Sets the tab stops of the writing.
+    ///
+    /// If `tab_stops` is `None`, tabs are drawn as ordinary glyphs.
+    pub fn set_tab_stops(&mut self, tab_stops: Option<TabStops>) {
+        if self.tab_stops != tab_stops {
+            self.tab_stops = tab_stops;
+            self.invalidate(Change::Layout);
+        }
+    }
+
+    /// Gets the decorations laid out with the writing.
+    #[must_use]
+    pub fn decorations(&self) -> Decorations {
+        self.decorations
+    }
+
+    /// Sets the decorations, such as underlines, laid out with the writing to `decorations`.
+    pub fn set_decorations(&mut self, decorations: Decorations) {
+        if self.decorations != decorations {
+            self.decorations = decorations;
+            self.invalidate(Change::Layout);
+        }
+    }
+
+    /// Applies these options to a layout `builder`.
+    pub(super) fn configure<'a>(&'a self, mut builder: Builder<'a>) -> Builder<'a> {
+        builder = builder
+            .with_alignment(self.alignment)
+            .with_line_height(self.line_height)
+            .with_paragraph_spacing(self.paragraph_spacing)
+            .with_long_words(self.long_words)
+            .with_decorations(self.decorations);
+        if let Some(line_alignment) = self.line_alignment {
+            builder = builder.with_line_alignment(line_alignment);
+        }
+        if let Some(max_width) = self.max_width {
+            builder = builder.with_max_width(max_width);
+        }
+        if let Some(truncation) = &self.truncation {
+            builder = builder.with_truncation(truncation);
+        }
+        if let Some(tab_stops) = &self.tab_stops {
+            builder = builder.with_tab_stops(tab_stops);
+        }
+        builder
+    }
+
+    /// Calculates where to put the top-left of a layout of the given `size` and `baseline`.
+    pub(super) fn top_left(
+        &self,
+        size: metrics::Size,
+        baseline: metrics::Length,
+    ) -> metrics::Point {
+        // The alignment offsets are the number of pixels between the top-left and the anchor, so
+        // we need to move so that the position (which is currently the top-left) is *on* that
+        // anchor.  This means the offsets must be backwards.
+        let mut top_left = self.pos;
+        top_left.x -= self.alignment.offset(size.w);
+        top_left.y -= self
+            .vertical_alignment
+            .offset_with_baseline(size.h, baseline);
+        top_left
+    }
+
+    /// Takes how much of the last layout these options have invalidated since the last call.
+    pub(super) fn take_change(&mut self) -> Change {
+        std::mem::take(&mut self.change)
+    }
+
+    fn invalidate(&mut self, change: Change) {
+        self.change = self.change.max(change);
+    }
+}
@@ -0,0 +1,359 @@
+//! Rich text, made up of spans that each carry their own fonts and colours.
+
+use std::mem;
+
+use crate::{error, font, metrics, render, resource::Map};
+
+/// A span of rich text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Span<FontId, FgId, BgId> {
+    /// The text of the span.
+    pub string: String,
+    /// The font chain used for the span.
+    pub fonts: font::Chain<FontId>,
+    /// The foreground colour of the span.
+    pub fg: FgId,
+    /// The background highlight of the span, if any.
+    pub bg: Option<BgId>,
+}
+
+impl<FontId, FgId, BgId> Span<FontId, FgId, BgId> {
+    /// Constructs a span of `string` with the given fonts and foreground colour, and no highlight.
+    pub fn new(
+        string: &(impl ToString + ?Sized),
+        fonts: impl Into<font::Chain<FontId>>,
+        fg: FgId,
+    ) -> Self {
+        Self {
+            string: string.to_string(),
+            fonts: fonts.into(),
+            fg,
+            bg: None,
+        }
+    }
+
+    /// Adds a background highlight to this span.
+    #[must_use]
+    pub fn with_bg(mut self, bg: BgId) -> Self {
+        self.bg = Some(bg);
+        self
+    }
+}
+
+/// A formatted renderer for rich text.
+///
+/// This works like [`super::Writer`], except that it writes a sequence of [Span]s, which are laid
+/// out continuously as if they were one string.  Lines are sized using the primary font of the
+/// first span.
+#[derive(Debug, Clone)]
+pub struct Writer<FontId, FgId, BgId> {
+    /// The options for positioning and laying out the writing.
+    options: super::Options,
+
+    /// The spans being written.
+    spans: Vec<Span<FontId, FgId, BgId>>,
+
+    /// The most recent layout of each span.
+    layouts: Vec<font::layout::String>,
+
+    /// Can we reuse the last computed layout?
+    layout_reusable: bool,
+}
+
+impl<FontId, FgId, BgId> Default for Writer<FontId, FgId, BgId> {
+    fn default() -> Self {
+        Self {
+            options: super::Options::default(),
+            spans: vec![],
+            layouts: vec![],
+            layout_reusable: false,
+        }
+    }
+}
+
+impl<FontId, FgId, BgId> Writer<FontId, FgId, BgId>
+where
+    FontId: Copy + Default,
+{
+    /// Lays out the current spans.
+    ///
+    /// As with [`super::Writer::layout`], this only does a full layout calculation if something
+    /// has changed since the last layout.
+    pub fn layout(&mut self, metrics: &impl Map<font::Metrics, Id = FontId>) {
+        let reusable = mem::replace(&mut self.layout_reusable, true);
+        let change = self.options.take_change();
+        if !reusable || change == super::options::Change::Layout {
+            self.actually_layout(metrics);
+        } else if change == super::options::Change::Position {
+            self.reposition_layout();
+        }
+    }
+
+    fn actually_layout(&mut self, metrics: &impl Map<font::Metrics, Id = FontId>) {
+        let primary = self
+            .spans
+            .first()
+            .map_or_else(FontId::default, |s| s.fonts.primary());
+        let builder = self
+            .options
+            .configure(font::layout::Builder::new(metrics.get(primary)));
+
+        let spans = self
+            .spans
+            .iter()
+            .map(|span| font::layout::Span {
+                string: span.string.clone(),
+                fonts: span.fonts.iter().map(|id| metrics.get(*id)).collect(),
+            })
+            .collect();
+        self.layouts = builder.build_spans(spans);
+        self.reposition_layout();
+    }
+}
+
+impl<FontId, FgId, BgId> Writer<FontId, FgId, BgId>
+where
+    FontId: Copy,
+    FgId: Copy,
+    BgId: Copy,
+{
+    /// Renders the most recently laid-out spans.
+    ///
+    /// All highlights are drawn before any text, so that highlights never cover text from
+    /// neighbouring spans.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the renderer can't blit glyphs or fills to the screen.
+    pub fn render(&self, r: &mut impl render::Renderer<FontId, FgId, BgId>) -> error::Result<()> {
+        for (span, layout) in self.spans.iter().zip(&self.layouts) {
+            if let Some(bg) = span.bg {
+                for rect in layout.selection(0..usize::MAX) {
+                    r.fill(rect, bg)?;
+                }
+            }
+        }
+
+        for (span, layout) in self.spans.iter().zip(&self.layouts) {
+            r.write(
+                &span.fonts,
+                span.fg,
+                layout,
+                metrics::point::Delta::default(),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<FontId, FgId, BgId> Writer<FontId, FgId, BgId> {
+    /// Gets the bounding box of the most recent layout.
+    #[must_use]
+    pub fn bounds(&self) -> metrics::Rect {
+        self.layouts.first().map(|l| l.bounds).unwrap_or_default()
+    }
+
+    /// Gets the spans of this writer.
+    #[must_use]
+    pub fn spans(&self) -> &[Span<FontId, FgId, BgId>] {
+        &self.spans
+    }
+
+    /// Sets the spans to be rendered to `spans`.
+    pub fn set_spans(&mut self, spans: Vec<Span<FontId, FgId, BgId>>)
+    where
+        FontId: PartialEq,
+        FgId: PartialEq,
+        BgId: PartialEq,
+    {
+        if self.spans != spans {
+            self.spans = spans;
+            self.layout_reusable = false;
+        }
+    }
+
+    /// Gets the positioning and layout options of this writer.
+    #[must_use]
+    pub fn options(&self) -> &super::Options {
+        &self.options
+    }
+
+    /// Gets the positioning and layout options of this writer, for changing them.
+    ///
+    /// Changes take effect the next time this writer is laid out.
+    pub fn options_mut(&mut self) -> &mut super::Options {
+        &mut self.options
+    }
+
+    /// Gets the position of this writer.
+    #[must_use]
+    pub fn pos(&self) -> metrics::Point {
+        self.options.pos()
+    }
+
+    /// Sets the position of this writer to `pos`.
+    pub fn move_to(&mut self, pos: metrics::Point) {
+        self.options.move_to(pos);
+    }
+
+    /// Gets the alignment of this writer.
+    #[must_use]
+    pub fn alignment(&self) -> metrics::anchor::X {
+        self.options.alignment()
+    }
+
+    /// Sets the alignment of this writer to `alignment`.
+    pub fn align_to(&mut self, alignment: metrics::anchor::X) {
+        self.options.align_to(alignment);
+    }
+
+    /// Gets the vertical alignment of this writer.
+    #[must_use]
+    pub fn vertical_alignment(&self) -> metrics::anchor::Y {
+        self.options.vertical_alignment()
+    }
+
+    /// Sets the vertical alignment of this writer to `alignment`.
+    pub fn valign_to(&mut self, alignment: metrics::anchor::Y) {
+        self.options.valign_to(alignment);
+    }
+
+    /// Gets the alignment of each line within this writer's text, if set separately.
+    #[must_use]
+    pub fn line_alignment(&self) -> Option<font::layout::LineAlignment> {
+        self.options.line_alignment()
+    }
+
+    /// Sets the alignment of each line within this writer's text to `alignment`.
+    pub fn set_line_alignment(&mut self, alignment: Option<font::layout::LineAlignment>) {
+        self.options.set_line_alignment(alignment);
+    }
+
+    /// Gets the distance between the tops of consecutive lines of this writer.
+    #[must_use]
+    pub fn line_height(&self) -> font::layout::LineHeight {
+        self.options.line_height()
+    }
+
+    /// Sets the distance between the tops of consecutive lines of this writer to `line_height`.
+    pub fn set_line_height(&mut self, line_height: font::layout::LineHeight) {
+        self.options.set_line_height(line_height);
+    }
+
+    /// Gets the extra space between paragraphs of this writer.
+    #[must_use]
+    pub fn paragraph_spacing(&self) -> metrics::Length {
+        self.options.paragraph_spacing()
+    }
+
+    /// Sets the extra space between paragraphs of this writer to `spacing`.
+    pub fn set_paragraph_spacing(&mut self, spacing: metrics::Length) {
+        self.options.set_paragraph_spacing(spacing);
+    }
+
+    /// Gets the width at which this writer wraps text, if any.
+    #[must_use]
+    pub fn max_width(&self) -> Option<metrics::Length> {
+        self.options.max_width()
+    }
+
+    /// Sets the width at which this writer wraps text at word boundaries to `max_width`.
+    pub fn wrap_to(&mut self, max_width: Option<metrics::Length>) {
+        self.options.wrap_to(max_width);
+    }
+
+    /// Sets how this writer wraps words too long to fit within its maximum width.
+    pub fn set_long_words(&mut self, long_words: font::layout::LongWords) {
+        self.options.set_long_words(long_words);
+    }
+
+    /// Gets how this writer truncates text wider than its maximum width, if it does.
+    #[must_use]
+    pub fn truncation(&self) -> Option<&font::layout::Truncation> {
+        self.options.truncation()
+    }
+
+    /// Sets whether, and how, this writer truncates text wider than its maximum width.
+    pub fn set_truncation(&mut self, truncation: Option<font::layout::Truncation>) {
+        self.options.set_truncation(truncation);
+    }
+
+    /// Sets the tab stops of this writer.
+    pub fn set_tab_stops(&mut self, tab_stops: Option<font::layout::TabStops>) {
+        self.options.set_tab_stops(tab_stops);
+    }
+
+    /// Gets the decorations laid out by this writer.
+    #[must_use]
+    pub fn decorations(&self) -> font::layout::Decorations {
+        self.options.decorations()
+    }
+
+    /// Sets the decorations, such as underlines, laid out by this writer to `decorations`.
+    pub fn set_decorations(&mut self, decorations: font::layout::Decorations) {
+        self.options.set_decorations(decorations);
+    }
+
+    /// Moves every span layout to the correct position.
+    fn reposition_layout(&mut self) {
+        let baseline = self.layouts.first().map_or(0, |l| l.baseline);
+        let top_left = self.options.top_left(self.bounds().size, baseline);
+
+        for layout in &mut self.layouts {
+            layout.bounds.top_left = top_left;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::{
+        render::logger::{
+            self,
+            Command::{Fill, Write},
+        },
+        resource::DefaultingHashMap,
+    };
+
+    /// Tests that spans flow together, and that highlights are filled before text is written.
+    #[test]
+    fn render_spans() {
+        let metrics = DefaultingHashMap::new(
+            HashMap::<(), _>::new(),
+            crate::backends::terminal::metrics(),
+        );
+
+        let mut writer = Writer::default();
+        writer.set_spans(vec![
+            Span::new("1:", (), 0),
+            Span::new("23", (), 1).with_bg(2),
+        ]);
+        writer.move_to(metrics::Point { x: 5, y: 3 });
+        writer.layout(&metrics);
+
+        let mut r: logger::Logger<(), u8, u8> = logger::Logger::default();
+        writer.render(&mut r).unwrap();
+
+        let [Fill(rect, 2), Write(_, 0, first), Write(_, 1, second)] = &r.log[..] else {
+            panic!("unexpected commands: {:?}", r.log);
+        };
+
+        assert_eq!(metrics::Rect::new(7, 3, 2, 1), *rect);
+        assert_eq!("1:", first.string);
+        assert_eq!("23", second.string);
+        assert_eq!(first.bounds, second.bounds);
+        assert_eq!(metrics::Size { w: 4, h: 1 }, second.bounds.size);
+
+        let mut dxs: Vec<_> = second
+            .glyphs
+            .into_iter()
+            .flat_map(|g| g.dsts.iter().map(|d| d.dx))
+            .collect();
+        dxs.sort_unstable();
+        assert_eq!(vec![2, 3], dxs);
+    }
+}
@@ -2,7 +2,13 @@
 
 use std::hash::Hash;
 
-use crate::{metrics, resource::Map, text::Writer, Renderer, Result};
+use crate::{
+    font::layout::{Decorations, LineAlignment, LineHeight, LongWords, TabStops, Truncation},
+    metrics,
+    resource::Map,
+    text::{effect::Effects, Writer},
+    Renderer, Result,
+};
 
 use super::super::{
     layout::{Boundable, LayoutContext, Layoutable},
@@ -10,7 +16,9 @@ use super::super::{
     update::Updatable,
 };
 
-/// A widget that displays a static single-line string with a static font.
+/// A widget that displays a static string with a static font.
+///
+/// Labels are single-line unless they are set to wrap at their bounds.
 ///
 /// `FontId`, `FgId`, and `BgId` are the usual font and colour ID types.
 #[derive(Clone)]
@@ -24,6 +32,9 @@ pub struct Label<FontId, FgId, BgId> {
     /// The background colour, if any.
     bg: Option<BgId>,
 
+    /// How to wrap the label's text at its bounds, if at all.
+    wrap: Option<LongWords>,
+
     /// The minimum amount of expected characters in the label.
     pub min_chars: u8,
 }
@@ -36,6 +47,7 @@ impl<FontId, FgId, BgId> Label<FontId, FgId, BgId> {
             bounds: metrics::Rect::default(),
             writer,
             bg: None,
+            wrap: None,
             min_chars: 0,
         }
     }
@@ -45,6 +57,25 @@ impl<FontId, FgId, BgId> Label<FontId, FgId, BgId> {
         self.writer.align_to(alignment);
     }
 
+    /// Sets the alignment of each line of the label, if different from the label's alignment.
+    ///
+    /// For example, a label aligned to the right of its bounds can still have its lines
+    /// left-aligned or justified relative to each other.
+    pub fn set_line_alignment(&mut self, alignment: Option<LineAlignment>) {
+        self.writer.set_line_alignment(alignment);
+    }
+
+    /// Sets the vertical alignment of the label.
+    ///
+    /// The label's text is placed at the given anchor of its bounds; for example, centring the
+    /// label centres its text vertically within its bounds.  Since the bounds have no baseline of
+    /// their own, baseline alignment places the text's baseline on the bottom of the bounds.
+    pub fn valign_to(&mut self, alignment: metrics::anchor::Y) {
+        self.writer.valign_to(alignment);
+        let pos = self.writer_pos();
+        self.writer.move_to(pos);
+    }
+
     /// Sets the minimum character amount of the label.
     pub fn set_min_chars(&mut self, amount: u8) {
         self.min_chars = amount;
@@ -55,6 +86,16 @@ impl<FontId, FgId, BgId> Label<FontId, FgId, BgId> {
         self.writer.set_fg(fg);
     }
 
+    /// Sets the text effects of the label.
+    pub fn set_effects(&mut self, effects: Effects<FgId>) {
+        self.writer.set_effects(effects);
+    }
+
+    /// Sets the decorations, such as underlines, of the label.
+    pub fn set_decorations(&mut self, decorations: Decorations) {
+        self.writer.set_decorations(decorations);
+    }
+
     /// Sets the foreground colour of the label.
     pub fn set_bg(&mut self, bg: impl Into<Option<BgId>>) {
         self.bg = bg.into();
@@ -65,6 +106,49 @@ impl<FontId, FgId, BgId> Label<FontId, FgId, BgId> {
         self.writer.set_font(font);
     }
 
+    /// Sets the font chain of the label, for glyphs missing from its primary font.
+    pub fn set_fonts(&mut self, fonts: impl Into<crate::font::Chain<FontId>>)
+    where
+        FontId: PartialEq,
+    {
+        self.writer.set_fonts(fonts);
+    }
+
+    /// Sets whether the label wraps its text at the width of its bounds.
+    ///
+    /// If `wrap` is `Some`, the label wraps at word boundaries, handling words longer than the
+    /// bounds as specified.  Otherwise, text past the bounds will overflow them.
+    pub fn set_wrap(&mut self, wrap: Option<LongWords>) {
+        self.wrap = wrap;
+        if let Some(long_words) = wrap {
+            self.writer.set_long_words(long_words);
+        }
+        self.update_wrap_width();
+    }
+
+    /// Sets whether, and how, the label truncates text that overflows the width of its bounds.
+    ///
+    /// Truncation takes priority over wrapping.
+    pub fn set_truncation(&mut self, truncation: Option<Truncation>) {
+        self.writer.set_truncation(truncation);
+        self.update_wrap_width();
+    }
+
+    /// Sets the distance between the tops of consecutive lines of the label.
+    pub fn set_line_height(&mut self, line_height: LineHeight) {
+        self.writer.set_line_height(line_height);
+    }
+
+    /// Sets the extra space between paragraphs of the label.
+    pub fn set_paragraph_spacing(&mut self, spacing: metrics::Length) {
+        self.writer.set_paragraph_spacing(spacing);
+    }
+
+    /// Sets the tab stops of the label, for lining up columns of text.
+    pub fn set_tab_stops(&mut self, tab_stops: Option<TabStops>) {
+        self.writer.set_tab_stops(tab_stops);
+    }
+
     /// Converts `str` to a string then updates the label with it.
     ///
     /// Does not re-layout the string, and so the string will not be updated until `layout` is
@@ -73,10 +157,15 @@ impl<FontId, FgId, BgId> Label<FontId, FgId, BgId> {
         self.writer.set_string(&str);
     }
 
+    fn update_wrap_width(&mut self) {
+        let clipped = self.wrap.is_some() || self.writer.truncation().is_some();
+        self.writer.wrap_to(clipped.then_some(self.bounds.size.w));
+    }
+
     fn writer_pos(&self) -> metrics::Point {
         self.bounds.anchor(metrics::anchor::Anchor {
             x: self.writer.alignment(),
-            y: metrics::anchor::Y::Top,
+            y: self.writer.vertical_alignment(),
         })
     }
 }
@@ -85,7 +174,9 @@ impl<FontId, FgId, BgId> Label<FontId, FgId, BgId> {
 impl<FontId, FgId, BgId> Boundable for Label<FontId, FgId, BgId> {
     fn set_bounds(&mut self, bounds: metrics::Rect) {
         self.bounds = bounds;
-        self.writer.move_to(self.writer_pos());
+        let pos = self.writer_pos();
+        self.writer.move_to(pos);
+        self.update_wrap_width();
     }
 }
 
@@ -96,9 +187,20 @@ where
     FontId: Copy + Clone + Default + Eq + Hash,
 {
     fn min_bounds(&self, ctx: &Ctx) -> metrics::Size {
-        ctx.font_metrics()
-            .get(self.writer.font)
-            .text_size(i32::from(self.min_chars), 1)
+        let font = ctx.font_metrics().get(self.writer.fonts.primary());
+        let size = font.text_size(i32::from(self.min_chars), 1);
+        if self.wrap.is_none() {
+            return size;
+        }
+        // A wrapping label needs room for its text wrapped to the minimum width, at the line height
+        // and paragraph spacing of the writer.  We can't wrap to the current bounds, as they
+        // depend on this measurement.
+        let line_h = self.writer.line_height().resolve(font);
+        let text_h = self.writer.measure_wrapped(ctx.font_metrics(), size.w).h;
+        metrics::Size {
+            w: size.w,
+            h: line_h.max(text_h),
+        }
     }
 
     fn layout(&mut self, ctx: &Ctx) {
@@ -20,12 +20,76 @@ fn test_small_font_metrics() {
     test_font("small")
 }
 
+/// Tests that scaling a font scales both its metrics and its texture.
+#[test]
+fn test_scaled_font() {
+    let font = font("medium");
+    let scaled = font.clone().scaled(3);
+
+    let m = font.metrics().expect("font must have metrics present");
+    let sm = scaled
+        .metrics()
+        .expect("scaled font must have metrics present");
+    assert_eq!(m.char.w * 3, sm.char.w);
+    assert_eq!(m.padded_h() * 3, sm.padded_h());
+    assert_eq!(m.span_w_str("Hello") * 3, sm.span_w_str("Hello"));
+
+    let t = font.texture().expect("font must have a texture present");
+    let st = scaled
+        .texture()
+        .expect("scaled font must have a texture present");
+    assert_eq!((t.width() * 3, t.height() * 3), st.dimensions());
+    assert_eq!(t.get_pixel(10, 10), st.get_pixel(31, 32));
+}
+
+/// Tests that packing a font and loading it back from memory gives the same font.
+#[test]
+fn test_packed_font() {
+    let font = font("medium");
+    let packed = ugly::Font::from_packed(font.pack().expect("font must pack"));
+
+    let m = font.metrics().expect("font must have metrics present");
+    let pm = packed
+        .metrics()
+        .expect("packed font must have metrics present");
+    assert_eq!(m.char, pm.char);
+    assert_eq!(m.span_w_str("Hello"), pm.span_w_str("Hello"));
+
+    let t = font.texture().expect("font must have a texture present");
+    let pt = packed
+        .texture()
+        .expect("packed font must have a texture present");
+    assert_eq!(t, pt);
+}
+
 fn test_font(name: &'static str) {
     let font = font(name);
     let m = font.metrics().expect("font must have metrics present");
     assert!(m.char.is_normal(), "character size must be normal");
     assert!(!m.char.is_zero(), "character size must be nonzero");
     assert!(m.pad.is_normal(), "padding must be normal");
+    assert!(
+        (0..=m.char.h).contains(&m.baseline),
+        "baseline must be within the character"
+    );
+    assert!(
+        m.ascent + m.descent <= m.char.h,
+        "ascent and descent must fit within the character"
+    );
+
+    // 'p' sits on the baseline and has a descender below it, which the descent must cover.
+    let t = font.texture().expect("font must have a texture present");
+    let sheet = ugly::font::analysis::Sheet::new(&m, &t);
+    let inked = |y| (0..m.char.w).any(|x| sheet.is_ink('p', x, y));
+    assert!(
+        inked(m.baseline - 1),
+        "'p' must have ink just above the baseline"
+    );
+    assert!(inked(m.baseline), "'p' must have ink below the baseline");
+    assert!(
+        (m.baseline + m.descent..m.char.h).all(|y| !inked(y)),
+        "'p' must not have ink below the descent"
+    );
 }
 
 fn font(name: &'static str) -> ugly::Font {
@@ -0,0 +1,156 @@
+//! Golden-image tests using the software renderer and the pack-in fonts.
+//!
+//! To regenerate the reference images, run these tests with `UGLY_UPDATE_SNAPSHOTS` set.
+
+use std::{collections::HashMap, path::PathBuf};
+
+use ugly::{
+    backends::software::snapshot,
+    colour::{self, ega},
+    font::layout::{Decorations, LineAlignment, LongWords, TabStop, TabStops},
+    metrics::{self, anchor},
+    resource,
+    text::{
+        effect::{Effects, Shadow},
+        Writer,
+    },
+    ui::{widgets::Label, Updatable},
+    Renderer,
+};
+
+/// Tests a label with a background, rendered as a widget tree.
+#[test]
+fn test_label() {
+    let mut label = Label::new(Writer::new((), ega::Id::BRIGHT_YELLOW));
+    label.set_bg(ega::Id::DARK_BLUE);
+    label.align_to(anchor::X::Centre);
+    label.update("Hello, world");
+
+    let size = metrics::Size { w: 96, h: 16 };
+    let image = snapshot::render_widget(size, resources(), &mut label).expect("render failed");
+    check(&image, "label");
+}
+
+/// Tests a label centred both horizontally and vertically within a taller cell.
+#[test]
+fn test_label_centred() {
+    let mut label = Label::new(Writer::new((), ega::Id::BRIGHT_WHITE));
+    label.set_bg(ega::Id::DARK_BLUE);
+    label.align_to(anchor::X::Centre);
+    label.valign_to(anchor::Y::Centre);
+    label.update("Centre");
+
+    let size = metrics::Size { w: 48, h: 24 };
+    let image = snapshot::render_widget(size, resources(), &mut label).expect("render failed");
+    check(&image, "label_centred");
+}
+
+/// Tests a label drawn with the medium font at double scale.
+#[test]
+fn test_label_scaled() {
+    let mut label = Label::new(Writer::new((), ega::Id::BRIGHT_WHITE));
+    label.set_bg(ega::Id::DARK_BLUE);
+    label.update("Big\ntext");
+
+    let size = metrics::Size { w: 64, h: 40 };
+    let image =
+        snapshot::render_widget(size, resources_at_scale(2), &mut label).expect("render failed");
+    check(&image, "label_scaled");
+}
+
+/// Tests a wrapped label whose lines are justified.
+#[test]
+fn test_label_justified() {
+    let mut label = Label::new(Writer::new((), ega::Id::BRIGHT_WHITE));
+    label.set_wrap(Some(LongWords::Overflow));
+    label.set_line_alignment(Some(LineAlignment::Justify(anchor::X::Left)));
+    label.update("Notes spread out across the whole panel.");
+
+    let size = metrics::Size { w: 100, h: 40 };
+    let image = snapshot::render_widget(size, resources(), &mut label).expect("render failed");
+    check(&image, "label_justified");
+}
+
+/// Tests a label lining up columns of split times with a decimal tab stop.
+#[test]
+fn test_label_tab_stops() {
+    let mut label = Label::new(Writer::new((), ega::Id::BRIGHT_WHITE));
+    label.set_tab_stops(Some(TabStops::new([TabStop::decimal(112, '.')])));
+    label.update("Intro\t1:23.45\nLong split\t12:03.9\nEnd\t-0.2");
+
+    let size = metrics::Size { w: 144, h: 32 };
+    let image = snapshot::render_widget(size, resources(), &mut label).expect("render failed");
+    check(&image, "label_tab_stops");
+}
+
+/// Tests a label with an outline, a drop shadow, and faux-bold.
+#[test]
+fn test_label_effects() {
+    let mut label = Label::new(Writer::new((), ega::Id::BRIGHT_WHITE));
+    label.set_effects(Effects {
+        outline: Some(ega::Id::DARK_BLACK),
+        shadow: Some(Shadow {
+            offset: metrics::point::Delta { dx: 1, dy: 2 },
+            colour: ega::Id::DARK_MAGENTA,
+        }),
+        bold: true,
+    });
+    label.align_to(anchor::X::Centre);
+    label.update("Effects");
+
+    let size = metrics::Size { w: 72, h: 16 };
+    let image = snapshot::render_widget(size, resources(), &mut label).expect("render failed");
+    check(&image, "label_effects");
+}
+
+/// Tests a wrapped label with underlines and strikethroughs spanning each line.
+#[test]
+fn test_label_decorations() {
+    let mut label = Label::new(Writer::new((), ega::Id::BRIGHT_YELLOW));
+    label.set_decorations(Decorations {
+        underline: true,
+        strikethrough: true,
+    });
+    label.set_wrap(Some(LongWords::Overflow));
+    label.update("Skipped split");
+
+    let size = metrics::Size { w: 40, h: 24 };
+    let image = snapshot::render_widget(size, resources(), &mut label).expect("render failed");
+    check(&image, "label_decorations");
+}
+
+/// Tests a sequence of raw renderer calls.
+#[test]
+fn test_fills() {
+    let size = metrics::Size { w: 16, h: 16 };
+    let image = snapshot::render(size, resources(), |r| {
+        r.clear(ega::Id::DARK_BLACK)?;
+        r.fill(metrics::Rect::new(2, 2, 8, 8), ega::Id::BRIGHT_RED)?;
+        r.fill(metrics::Rect::new(6, 6, 8, 8), ega::Id::BRIGHT_GREEN)
+    })
+    .expect("render failed");
+    check(&image, "fills");
+}
+
+fn check(image: &image::RgbaImage, name: &str) {
+    let path: PathBuf = ["tests", "snapshots", &format!("{name}.png")]
+        .iter()
+        .collect();
+    if let Err(e) = snapshot::check(image, path) {
+        panic!("snapshot mismatch: {e}");
+    }
+}
+
+fn resources(
+) -> resource::Set<resource::DefaultingHashMap<(), ugly::Font>, colour::Ega, colour::Ega> {
+    resources_at_scale(1)
+}
+
+fn resources_at_scale(
+    scale: u8,
+) -> resource::Set<resource::DefaultingHashMap<(), ugly::Font>, colour::Ega, colour::Ega> {
+    let path: PathBuf = ["assets", "fonts", "medium"].iter().collect();
+    let font = ugly::Font::from_dir(path).scaled(scale);
+    let fonts = resource::DefaultingHashMap::new(HashMap::from([((), font.clone())]), font);
+    resource::Set::new(fonts, colour::EGA, colour::EGA).expect("font metrics should load")
+}
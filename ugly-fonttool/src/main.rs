@@ -0,0 +1,143 @@
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand};
+
+use ugly::font::{analysis, import};
+
+/// Tools for making and maintaining ugly fonts
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+struct Args {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Converts a BDF bitmap font into a font directory.
+    Bdf {
+        /// The BDF file to convert.
+        input: PathBuf,
+        /// The font directory to write.
+        output: PathBuf,
+    },
+    /// Bakes a TrueType or OpenType font into a font directory at a fixed pixel size.
+    Bake {
+        /// The font file to bake.
+        input: PathBuf,
+        /// The font directory to write.
+        output: PathBuf,
+        /// The size to bake at, in pixels per em.
+        #[arg(short, long)]
+        size: i32,
+        /// The characters to bake (defaults to the printable characters of ISO 8859-1).
+        #[arg(short, long)]
+        chars: Option<String>,
+    },
+    /// Infers width overrides from the glyphs in a font directory's texture.
+    Widths {
+        /// The font directory to inspect.
+        font: PathBuf,
+        /// Instead of printing overrides, report glyphs whose widths clip their ink.
+        #[arg(short, long)]
+        check: bool,
+    },
+    /// Suggests kerning pairs for glyphs in a font directory that can tuck together.
+    Kerning {
+        /// The font directory to inspect.
+        font: PathBuf,
+        /// The most pixels closer than the default spacing to move any pair.
+        #[arg(short, long, default_value_t = 2)]
+        max_tuck: i32,
+    },
+    /// Packs a font directory, or PSF console font, into a single packed font file.
+    Pack {
+        /// The font directory or PSF file to pack.
+        input: PathBuf,
+        /// The packed font file to write.
+        output: PathBuf,
+    },
+    /// Converts a PSF1 or PSF2 console font into a font directory.
+    Psf {
+        /// The PSF file to convert.
+        input: PathBuf,
+        /// The font directory to write.
+        output: PathBuf,
+    },
+}
+
+fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+
+    match args.command {
+        Command::Bdf { input, output } => {
+            let bdf = std::fs::read_to_string(input)?;
+            let font = import::bdf::parse(&bdf)?;
+            font.save(output)?;
+        }
+        Command::Bake {
+            input,
+            output,
+            size,
+            chars,
+        } => {
+            let ttf = std::fs::read(input)?;
+            let font = match chars {
+                Some(chars) => import::truetype::bake(&ttf, size, chars.chars()),
+                None => import::truetype::bake(&ttf, size, import::truetype::latin1()),
+            }?;
+            font.save(output)?;
+        }
+        Command::Psf { input, output } => {
+            let psf = std::fs::read(input)?;
+            let font = import::psf::parse(&psf)?;
+            font.save(output)?;
+        }
+        Command::Pack { input, output } => {
+            let font = if input.is_file() {
+                ugly::Font::from_psf(input)
+            } else {
+                ugly::Font::from_dir(input)
+            };
+            std::fs::write(output, font.pack()?)?;
+        }
+        Command::Widths { font, check } => widths(&ugly::Font::from_dir(font), check)?,
+        Command::Kerning { font, max_tuck } => {
+            let font = ugly::Font::from_dir(font);
+            let metrics = font.metrics()?;
+            let texture = font.texture()?;
+            let sheet = analysis::Sheet::new(&metrics, &texture);
+            print_ron(&analysis::kerning::suggest(&sheet, max_tuck))?;
+        }
+    }
+
+    Ok(())
+}
+
+fn widths(font: &ugly::Font, check: bool) -> anyhow::Result<()> {
+    let metrics = font.metrics()?;
+    let texture = font.texture()?;
+    let sheet = analysis::Sheet::new(&metrics, &texture);
+
+    if !check {
+        return print_ron(&analysis::width::infer(&sheet));
+    }
+
+    let clipped = analysis::width::check(&sheet);
+    for c in &clipped {
+        println!(
+            "{:?}: width {} clips ink {} pixels wide",
+            c.char, c.width, c.ink_width
+        );
+    }
+    if !clipped.is_empty() {
+        anyhow::bail!("{} glyphs have clipped ink", clipped.len());
+    }
+    Ok(())
+}
+
+fn print_ron(value: &impl serde::Serialize) -> anyhow::Result<()> {
+    let config = ron::ser::PrettyConfig::default();
+    println!("{}", ron::ser::to_string_pretty(value, config)?);
+    Ok(())
+}
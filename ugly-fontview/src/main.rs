@@ -39,7 +39,7 @@ struct Args {
     #[arg(short = 'f', long, group = "input")]
     text_file: Option<PathBuf>,
 
-    /// Directory of font to load
+    /// Directory of font to load, or a packed font or PSF console font file
     #[arg(short = 'F', long, default_value = "../assets/fonts/medium")]
     font: PathBuf,
 
@@ -203,7 +203,18 @@ fn main() -> anyhow::Result<()> {
 type FontMap = ugly::resource::DefaultingHashMap<usize, ugly::Font>;
 
 fn get_fonts(path: &std::path::Path) -> ugly::resource::DefaultingHashMap<usize, ugly::Font> {
-    let font = font::Font::from_dir(path);
+    let packed = path
+        .is_file()
+        .then(|| std::fs::read(path).ok())
+        .flatten()
+        .filter(|bytes| bytes.starts_with(&font::pack::MAGIC));
+    let font = if let Some(bytes) = packed {
+        font::Font::from_packed(bytes)
+    } else if path.is_file() {
+        font::Font::from_psf(path)
+    } else {
+        font::Font::from_dir(path)
+    };
 
     let mut map: HashMap<usize, _> = HashMap::new();
     map.insert(0, font.clone());